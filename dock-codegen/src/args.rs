@@ -1,8 +1,54 @@
-use syn::{AttributeArgs, LitBool, LitStr, NestedMeta};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{AttributeArgs, Lit, LitBool, LitStr, Meta, NestedMeta};
 pub struct CommandArgs {
     pub name: Option<LitStr>,
     pub description: Option<LitStr>,
     pub disabled: Option<LitBool>,
+    pub category: Option<LitStr>,
+    pub examples: Vec<TokenStream>,
+    pub output: bool,
+}
+
+/// Parse `example = "..."` or `example("...", "caption")` into the construction of a `dock::command::Example`
+///
+/// Returns `None` if the attribute is not an example.
+pub fn example(meta: &Meta) -> syn::Result<Option<TokenStream>> {
+    if !meta.path().is_ident("example") {
+        return Ok(None);
+    }
+
+    let lits: Vec<&Lit> = match meta {
+        Meta::NameValue(nv) => vec![&nv.lit],
+        Meta::List(list) => list
+            .nested
+            .iter()
+            .map(|nested| match nested {
+                NestedMeta::Lit(lit) => Ok(lit),
+                nested => Err(syn::Error::new_spanned(
+                    nested,
+                    "Attribute example expects literal strings!",
+                )),
+            })
+            .collect::<syn::Result<_>>()?,
+        Meta::Path(path) => {
+            return Err(syn::Error::new_spanned(
+                path,
+                "Attribute example expects an invocation!",
+            ))
+        }
+    };
+
+    match lits.as_slice() {
+        [Lit::Str(command)] => Ok(Some(quote! { ::dock::command::Example::new(#command) })),
+        [Lit::Str(command), Lit::Str(caption)] => Ok(Some(quote! {
+            ::dock::command::Example::new(#command).set_caption(#caption)
+        })),
+        _ => Err(syn::Error::new_spanned(
+            meta,
+            "Attribute example expects an invocation and an optional caption!",
+        )),
+    }
 }
 
 impl CommandArgs {
@@ -10,9 +56,20 @@ impl CommandArgs {
         let mut name = None;
         let mut description = None;
         let mut disabled = None;
+        let mut category = None;
+        let mut examples = vec![];
+        let mut output = false;
 
         for arg in args {
+            if let NestedMeta::Meta(meta) = &arg {
+                if let Some(example) = example(meta)? {
+                    examples.push(example);
+                    continue;
+                }
+            }
+
             match arg {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("output") => output = true,
                 NestedMeta::Meta(syn::Meta::NameValue(nv)) => {
                     if nv.path.is_ident("name") {
                         if let syn::Lit::Str(lit) = nv.lit {
@@ -32,6 +89,15 @@ impl CommandArgs {
                                 "Attribute description expects literal string!",
                             ));
                         }
+                    } else if nv.path.is_ident("category") {
+                        if let syn::Lit::Str(lit) = nv.lit {
+                            category = Some(lit);
+                        } else {
+                            return Err(syn::Error::new_spanned(
+                                nv.lit,
+                                "Attribute category expects literal string!",
+                            ));
+                        }
                     } else if nv.path.is_ident("disabled") {
                         if let syn::Lit::Bool(lit) = nv.lit {
                             disabled = Some(lit)
@@ -46,6 +112,9 @@ impl CommandArgs {
             name,
             description,
             disabled,
+            category,
+            examples,
+            output,
         })
     }
 }
@@ -0,0 +1,309 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    Attribute, Data, DeriveInput, Field, Fields, GenericArgument, Lit, Meta, NestedMeta,
+    PathArguments, Type,
+};
+
+/// The shape of a field, which decides how its argument is parsed
+enum FieldKind {
+    Flatten,
+    Flag,
+    Count,
+    Optional(Type),
+    Multiple(Type),
+    Required(Type),
+}
+
+/// Holds the values of the `arg` attributes on a field
+#[derive(Default)]
+struct FieldArgs {
+    short: Option<char>,
+    long: Option<String>,
+    help: Option<String>,
+    value_name: Option<String>,
+    default: Option<String>,
+    env: Option<String>,
+    flatten: bool,
+    count: bool,
+}
+
+impl FieldArgs {
+    fn new(field: &Field, id: &str) -> syn::Result<Self> {
+        let mut args = Self {
+            help: doc_comment(&field.attrs),
+            ..Default::default()
+        };
+
+        for attr in field.attrs.iter().filter(|attr| attr.path.is_ident("arg")) {
+            let list = match attr.parse_meta()? {
+                Meta::List(list) => list,
+                meta => return Err(syn::Error::new_spanned(meta, "Expected #[arg(...)]")),
+            };
+
+            for nested in list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::Path(path)) => {
+                        if path.is_ident("short") {
+                            args.short = id.chars().next();
+                        } else if path.is_ident("long") {
+                            args.long = Some(id.replace('_', "-"));
+                        } else if path.is_ident("flatten") {
+                            args.flatten = true;
+                        } else if path.is_ident("count") {
+                            args.count = true;
+                        } else {
+                            return Err(syn::Error::new_spanned(path, "Unknown attribute"));
+                        }
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) => {
+                        if nv.path.is_ident("short") {
+                            if let Lit::Char(lit) = nv.lit {
+                                args.short = Some(lit.value());
+                            } else {
+                                return Err(syn::Error::new_spanned(
+                                    nv.lit,
+                                    "Attribute short expects literal char!",
+                                ));
+                            }
+                        } else if nv.path.is_ident("default") {
+                            args.default = Some(match nv.lit {
+                                Lit::Str(lit) => lit.value(),
+                                Lit::Int(lit) => lit.base10_digits().to_string(),
+                                Lit::Float(lit) => lit.base10_digits().to_string(),
+                                Lit::Bool(lit) => lit.value.to_string(),
+                                Lit::Char(lit) => lit.value().to_string(),
+                                lit => {
+                                    return Err(syn::Error::new_spanned(
+                                        lit,
+                                        "Attribute default expects a literal!",
+                                    ))
+                                }
+                            });
+                        } else {
+                            let value = match nv.lit {
+                                Lit::Str(lit) => lit.value(),
+                                lit => {
+                                    return Err(syn::Error::new_spanned(
+                                        lit,
+                                        "Attribute expects literal string!",
+                                    ))
+                                }
+                            };
+
+                            if nv.path.is_ident("long") {
+                                args.long = Some(value);
+                            } else if nv.path.is_ident("help") {
+                                args.help = Some(value);
+                            } else if nv.path.is_ident("value_name") {
+                                args.value_name = Some(value);
+                            } else if nv.path.is_ident("env") {
+                                args.env = Some(value);
+                            } else {
+                                return Err(syn::Error::new_spanned(nv.path, "Unknown attribute"));
+                            }
+                        }
+                    }
+                    nested => return Err(syn::Error::new_spanned(nested, "Unknown attribute")),
+                }
+            }
+        }
+
+        Ok(args)
+    }
+
+    fn kind(&self, ty: &Type) -> FieldKind {
+        if self.flatten {
+            FieldKind::Flatten
+        } else if self.count {
+            FieldKind::Count
+        } else if is_ident(ty, "bool") {
+            FieldKind::Flag
+        } else if let Some(inner) = generic_inner(ty, "Option") {
+            FieldKind::Optional(inner)
+        } else if let Some(inner) = generic_inner(ty, "Vec") {
+            FieldKind::Multiple(inner)
+        } else {
+            FieldKind::Required(ty.clone())
+        }
+    }
+}
+
+/// Join the doc comments of an item into a single line
+pub fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(nv)) => match nv.lit {
+                Lit::Str(lit) => Some(lit.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<String>>();
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join(" "))
+    }
+}
+
+fn is_ident(ty: &Type, ident: &str) -> bool {
+    match ty {
+        Type::Path(path) => path.qself.is_none() && path.path.is_ident(ident),
+        _ => false,
+    }
+}
+
+fn generic_inner(ty: &Type, ident: &str) -> Option<Type> {
+    let segment = match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last()?,
+        _ => return None,
+    };
+
+    if segment.ident != ident {
+        return None;
+    }
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(inner) => Some(inner.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "Args can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "Args can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut specs = vec![];
+    let mut values = vec![];
+
+    for field in fields {
+        let ident = field.ident.clone().unwrap();
+        let id = ident.to_string();
+        let ty = &field.ty;
+        let args = FieldArgs::new(field, &id)?;
+
+        let mut spec = quote! { ::dock::arg::Arg::new(#id) };
+
+        if let Some(short) = args.short {
+            spec = quote! { #spec.set_short(#short) };
+        }
+        if let Some(long) = &args.long {
+            spec = quote! { #spec.set_long(#long) };
+        }
+        if let Some(help) = &args.help {
+            spec = quote! { #spec.set_help(#help) };
+        }
+        if let Some(value_name) = &args.value_name {
+            spec = quote! { #spec.set_value_name(#value_name) };
+        }
+        if let Some(default) = &args.default {
+            spec = quote! { #spec.set_default(#default) };
+        }
+        if let Some(env) = &args.env {
+            spec = quote! { #spec.set_env(#env) };
+        }
+
+        let validator = |inner: &syn::Type| {
+            quote! {
+                .set_validator(|value: &str| {
+                    value
+                        .parse::<#inner>()
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+            }
+        };
+
+        let kind = args.kind(ty);
+        if matches!(kind, FieldKind::Flag | FieldKind::Count) && args.default.is_some() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "Attribute default is not supported on flags!",
+            ));
+        }
+
+        let (spec, value) = match kind {
+            FieldKind::Flatten => (
+                quote! { <#ty as ::dock::arg::Args>::spec() },
+                quote! { <#ty as ::dock::arg::Args>::from_matches(matches)? },
+            ),
+            FieldKind::Flag => (
+                quote! { vec![#spec.set_takes_value(false)] },
+                quote! { matches.is_present(#id) },
+            ),
+            FieldKind::Count => (
+                quote! { vec![#spec.set_takes_value(false)] },
+                quote! {
+                    ::std::convert::TryFrom::try_from(matches.occurrences(#id)).unwrap_or(<#ty>::MAX)
+                },
+            ),
+            FieldKind::Optional(inner) => (
+                {
+                    let validator = validator(&inner);
+                    quote! { vec![#spec #validator] }
+                },
+                quote! { matches.get::<#inner>(#id)? },
+            ),
+            FieldKind::Multiple(inner) => (
+                {
+                    let validator = validator(&inner);
+                    quote! { vec![#spec.set_multiple(true) #validator] }
+                },
+                quote! { matches.get_many::<#inner>(#id)? },
+            ),
+            FieldKind::Required(inner) => {
+                let required = args.default.is_none();
+                let validator = validator(&inner);
+                (
+                    quote! { vec![#spec.set_required(#required) #validator] },
+                    quote! { matches.get_required::<#inner>(#id)? },
+                )
+            }
+        };
+
+        specs.push(spec);
+        values.push(quote! { #ident: #value });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::dock::arg::Args for #name #ty_generics #where_clause {
+            fn spec() -> Vec<::dock::arg::Arg> {
+                let specs: Vec<Vec<::dock::arg::Arg>> = vec![#(#specs),*];
+                specs.concat()
+            }
+
+            #[allow(unused_variables)]
+            fn from_matches(matches: &::dock::arg::ArgMatches) -> ::dock::error::Result<Self> {
+                Ok(Self {
+                    #(#values),*
+                })
+            }
+        }
+    })
+}
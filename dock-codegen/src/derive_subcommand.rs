@@ -0,0 +1,183 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Lit, Meta, NestedMeta, Variant};
+
+use crate::{args::example, derive_args::doc_comment};
+
+/// Holds the values of the `subcommand` attributes on a variant
+struct VariantArgs {
+    name: String,
+    disabled: bool,
+    category: Option<String>,
+    examples: Vec<TokenStream>,
+}
+
+impl VariantArgs {
+    fn new(variant: &Variant) -> syn::Result<Self> {
+        let mut args = Self {
+            name: kebab_case(&variant.ident.to_string()),
+            disabled: false,
+            category: None,
+            examples: vec![],
+        };
+
+        for attr in variant
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("subcommand"))
+        {
+            let list = match attr.parse_meta()? {
+                Meta::List(list) => list,
+                meta => return Err(syn::Error::new_spanned(meta, "Expected #[subcommand(...)]")),
+            };
+
+            for nested in list.nested {
+                if let NestedMeta::Meta(meta) = &nested {
+                    if let Some(example) = example(meta)? {
+                        args.examples.push(example);
+                        continue;
+                    }
+                }
+
+                match nested {
+                    NestedMeta::Meta(Meta::Path(path)) if path.is_ident("disabled") => {
+                        args.disabled = true;
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("name") => {
+                        if let Lit::Str(lit) = nv.lit {
+                            args.name = lit.value();
+                        } else {
+                            return Err(syn::Error::new_spanned(
+                                nv.lit,
+                                "Attribute name expects literal string!",
+                            ));
+                        }
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("category") => {
+                        if let Lit::Str(lit) = nv.lit {
+                            args.category = Some(lit.value());
+                        } else {
+                            return Err(syn::Error::new_spanned(
+                                nv.lit,
+                                "Attribute category expects literal string!",
+                            ));
+                        }
+                    }
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("disabled") => {
+                        if let Lit::Bool(lit) = nv.lit {
+                            args.disabled = lit.value;
+                        } else {
+                            return Err(syn::Error::new_spanned(
+                                nv.lit,
+                                "Attribute disabled expects literal bool!",
+                            ));
+                        }
+                    }
+                    nested => return Err(syn::Error::new_spanned(nested, "Unknown attribute")),
+                }
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+/// Convert a variant identifier such as `BuildAll` into a command name such as `build-all`
+///
+/// Acronyms are kept together, so that `HTTPServer` becomes `http-server`.
+fn kebab_case(ident: &str) -> String {
+    let chars = ident.chars().collect::<Vec<char>>();
+    let mut name = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next = chars.get(i + 1);
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase()))
+            {
+                name.push('-');
+            }
+        }
+        name.extend(c.to_lowercase());
+    }
+
+    name
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "Subcommand can only be derived for enums",
+            ))
+        }
+    };
+
+    let mut infos = vec![];
+    let mut arms = vec![];
+
+    for variant in variants {
+        let ident = &variant.ident;
+        let args = VariantArgs::new(variant)?;
+        let name = &args.name;
+        let disabled = args.disabled;
+        let category = match &args.category {
+            Some(category) => quote! { Some(#category.to_string()) },
+            None => quote! { None },
+        };
+        let examples = &args.examples;
+        let description = doc_comment(&variant.attrs).unwrap_or_default();
+
+        let (spec, value) = match &variant.fields {
+            Fields::Unit => (quote! { vec![] }, quote! { Self::#ident }),
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let ty = &fields.unnamed[0].ty;
+                (
+                    quote! { <#ty as ::dock::arg::Args>::spec() },
+                    quote! { Self::#ident(<#ty as ::dock::arg::Args>::from_matches(matches)?) },
+                )
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "Subcommand variants must be units or hold a single Args struct",
+                ))
+            }
+        };
+
+        infos.push(quote! {
+            ::dock::subcommand::Variant {
+                name: #name.to_string(),
+                description: #description.to_string(),
+                disabled: #disabled,
+                category: #category,
+                examples: vec![#(#examples),*],
+                args: #spec,
+            }
+        });
+        arms.push(quote! { #name => Ok(#value) });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::dock::subcommand::Subcommand for #name #ty_generics #where_clause {
+            fn variants() -> Vec<::dock::subcommand::Variant> {
+                vec![#(#infos),*]
+            }
+
+            #[allow(unused_variables)]
+            fn from_matches(name: &str, matches: &::dock::arg::ArgMatches) -> ::dock::error::Result<Self> {
+                match name {
+                    #(#arms,)*
+                    _ => Err(::dock::error::Error::UnknownCommand(name.to_string())),
+                }
+            }
+        }
+    })
+}
@@ -1,18 +1,38 @@
 mod args;
+mod derive_args;
+mod derive_subcommand;
 
 use args::CommandArgs;
 #[allow(unused)]
 use dock::command::Command;
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, AttributeArgs, ItemFn};
+use syn::{parse_macro_input, AttributeArgs, DeriveInput, FnArg, ItemFn, Type};
+
+/// Whether the parameter of a command callback receives the `Context` rather than a group of arguments
+fn is_context(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => match &*reference.elem {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Context"),
+            _ => false,
+        },
+        _ => false,
+    }
+}
 
 #[proc_macro_attribute]
 pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
     let input_fn = parse_macro_input!(item as ItemFn);
     let attr_args = parse_macro_input!(attr as AttributeArgs);
 
-    let parsed_args = CommandArgs::new(attr_args).unwrap();
+    let parsed_args = match CommandArgs::new(attr_args) {
+        Ok(parsed_args) => parsed_args,
+        Err(err) => return err.to_compile_error().into(),
+    };
 
     let fn_name = input_fn.clone().sig.ident;
 
@@ -32,7 +52,37 @@ pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
         disabled = val.value()
     }
 
-    let fn_name = input_fn.clone().sig.ident;
+    let category = match parsed_args.category {
+        Some(val) => quote! { Some(#val.to_string()) },
+        None => quote! { None },
+    };
+
+    let examples = &parsed_args.examples;
+
+    let mut arg_types = vec![];
+    let mut call_args = vec![];
+
+    for input in &input_fn.sig.inputs {
+        match input {
+            FnArg::Typed(pat) if is_context(&pat.ty) => call_args.push(quote! { ctx }),
+            FnArg::Typed(pat) => {
+                let ty = &pat.ty;
+                arg_types.push(ty.clone());
+                call_args.push(quote! { <#ty as ::dock::arg::Args>::from_context(ctx)? });
+            }
+            FnArg::Receiver(receiver) => {
+                return syn::Error::new_spanned(receiver, "Commands cannot take self")
+                    .to_compile_error()
+                    .into()
+            }
+        }
+    }
+
+    let result = if parsed_args.output {
+        quote! { ::dock::__render_output!(ctx, #fn_name(#(#call_args),*)) }
+    } else {
+        quote! { ::dock::error::IntoResult::into_result(#fn_name(#(#call_args),*)) }
+    };
 
     TokenStream::from(quote! {
 
@@ -41,7 +91,7 @@ pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
         pub struct #fn_name;
 
 
-        impl Command for #fn_name{
+        impl ::dock::command::Command for #fn_name{
 
             fn name(&self) -> String{
 
@@ -58,13 +108,45 @@ pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
                 #disabled
             }
 
-            fn call(&self){
+            fn category(&self) -> Option<String>{
+                #category
+            }
+
+            fn examples(&self) -> Vec<::dock::command::Example>{
+                vec![#(#examples),*]
+            }
+
+            fn args(&self) -> Vec<::dock::arg::Arg>{
+                let specs: Vec<Vec<::dock::arg::Arg>> = vec![#(<#arg_types as ::dock::arg::Args>::spec()),*];
+                specs.concat()
+            }
+
+            #[allow(unused_variables)]
+            fn call(&self, ctx: &::dock::context::Context) -> ::dock::error::Result{
                 #input_fn
 
-                #fn_name()
+                #result
             }
 
 
         }
     })
 }
+
+#[proc_macro_derive(Args, attributes(arg))]
+pub fn derive_args(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+
+    derive_args::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(Subcommand, attributes(subcommand))]
+pub fn derive_subcommand(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+
+    derive_subcommand::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,101 @@
+use dock::{
+    arg::{ArgMatches, Args},
+    context::Context,
+    App,
+};
+
+use dock_codegen::{command, Args};
+
+#[derive(Args)]
+struct GlobalOpts {
+    /// Print more output
+    #[arg(short, long, count)]
+    verbose: u8,
+}
+
+#[derive(Args)]
+struct BuildArgs {
+    /// The target to build
+    target: String,
+    /// Number of parallel jobs
+    #[arg(short, long, default = 4)]
+    jobs: usize,
+    /// Build in release mode
+    #[arg(short, long)]
+    release: bool,
+    #[arg(long, value_name = "DIR")]
+    out_dir: Option<String>,
+    /// Files to build
+    files: Vec<String>,
+    #[arg(flatten)]
+    global: GlobalOpts,
+}
+
+#[test]
+fn test_derive_args() {
+    let spec = BuildArgs::spec();
+
+    assert_eq!(
+        spec.iter().map(|arg| arg.id.as_str()).collect::<Vec<_>>(),
+        vec!["target", "jobs", "release", "out_dir", "files", "verbose"]
+    );
+    assert_eq!(spec[1].help, "Number of parallel jobs");
+    assert_eq!(spec[3].long.as_deref(), Some("out-dir"));
+    assert!(spec[0].required && !spec[1].required);
+
+    let mut matches = ArgMatches::new();
+    matches.add_value("target", "x86");
+    matches.add_value("jobs", "8");
+    matches.add_occurrence("verbose");
+    matches.add_occurrence("verbose");
+
+    let args = BuildArgs::from_matches(&matches).unwrap();
+
+    assert_eq!(args.target, "x86");
+    assert_eq!(args.jobs, 8);
+    assert!(!args.release);
+    assert_eq!(args.out_dir, None);
+    assert!(args.files.is_empty());
+    assert_eq!(args.global.verbose, 2);
+}
+
+#[test]
+fn test_command_with_args() {
+    #[command(description = "Build the project")]
+    fn build(ctx: &Context, args: BuildArgs) -> Result<(), String> {
+        assert_eq!(ctx.command.name(), "build");
+        assert_eq!(args.target, "wasm");
+        assert_eq!(args.jobs, 4);
+        assert!(args.release);
+        assert_eq!(args.files, vec!["a.rs".to_string(), "b.rs".to_string()]);
+        assert_eq!(args.global.verbose, 3);
+
+        Ok(())
+    }
+
+    #[command]
+    fn fail() -> Result<(), String> {
+        Err("it failed".to_string())
+    }
+
+    let app = || App::new().register_command(build).register_command(fail);
+
+    app()
+        .run_from([
+            "tool",
+            "build",
+            "-vv",
+            "wasm",
+            "--release",
+            "a.rs",
+            "-v",
+            "b.rs",
+        ])
+        .unwrap();
+
+    assert!(app().run_from(["tool", "build"]).is_err());
+    assert_eq!(
+        app().run_from(["tool", "fail"]).unwrap_err().to_string(),
+        "it failed"
+    );
+}
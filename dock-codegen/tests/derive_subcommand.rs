@@ -0,0 +1,92 @@
+use std::cell::RefCell;
+
+use dock::{
+    arg::ArgMatches,
+    context::Context,
+    error::Result,
+    subcommand::{Execute, Subcommand},
+    App,
+};
+
+use dock_codegen::{Args, Subcommand};
+
+thread_local! {
+    static EXECUTED: RefCell<Vec<String>> = const { RefCell::new(vec![]) };
+}
+
+#[derive(Args)]
+struct BuildArgs {
+    /// The target to build
+    target: String,
+    #[arg(short, long)]
+    release: bool,
+}
+
+#[derive(Subcommand)]
+enum Ops {
+    /// Build the project
+    #[subcommand(example = "tool build wasm --release")]
+    Build(BuildArgs),
+    /// Remove build artifacts
+    #[subcommand(name = "clean", category = "Maintenance")]
+    CleanAll,
+    #[subcommand(disabled)]
+    SelfDestruct,
+    /// Serve the build over HTTP
+    HTTPServer,
+    /// Upgrade to the latest version
+    UpgradeV2Api,
+}
+
+impl Execute for Ops {
+    fn execute(self, _ctx: &Context) -> Result {
+        let executed = match self {
+            Ops::Build(args) => format!("build {} {}", args.target, args.release),
+            Ops::CleanAll => "clean".to_string(),
+            Ops::SelfDestruct => "self-destruct".to_string(),
+            Ops::HTTPServer => "http-server".to_string(),
+            Ops::UpgradeV2Api => "upgrade-v2-api".to_string(),
+        };
+        EXECUTED.with(|cell| cell.borrow_mut().push(executed));
+        Ok(())
+    }
+}
+
+#[test]
+fn test_derive_subcommand() {
+    let variants = Ops::variants();
+
+    assert_eq!(
+        variants
+            .iter()
+            .map(|variant| variant.name.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "build",
+            "clean",
+            "self-destruct",
+            "http-server",
+            "upgrade-v2-api"
+        ]
+    );
+    assert_eq!(variants[0].description, "Build the project");
+    assert_eq!(variants[0].args.len(), 2);
+    assert!(variants[2].disabled);
+    assert_eq!(variants[1].category.as_deref(), Some("Maintenance"));
+    assert!(Ops::from_matches("unknown", &ArgMatches::new()).is_err());
+
+    let app = || App::new().register_subcommands::<Ops>();
+    app().check_examples().unwrap();
+
+    app().run_from(["tool", "build", "wasm", "-r"]).unwrap();
+    app().run_from(["tool", "clean"]).unwrap();
+    app().run_from(["tool", "http-server"]).unwrap();
+    assert!(app().run_from(["tool", "self-destruct"]).is_err());
+
+    EXECUTED.with(|cell| {
+        assert_eq!(
+            *cell.borrow(),
+            vec!["build wasm true", "clean", "http-server"]
+        );
+    });
+}
@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+
+use dock::{
+    context::{Context, Environment},
+    App,
+};
+
+use dock_codegen::{command, Args};
+
+#[derive(Args)]
+struct DeployArgs {
+    #[arg(long, env = "DEPLOY_TOKEN")]
+    token: String,
+}
+
+#[test]
+fn test_environment() {
+    #[command]
+    fn deploy(ctx: &Context, args: DeployArgs) {
+        assert_eq!(args.token, "secret");
+        assert_eq!(ctx.env.env_var("REGION"), Some("eu"));
+        assert_eq!(ctx.env.current_dir, Some(PathBuf::from("/srv/app")));
+        assert_eq!(ctx.env.argv.len(), 2);
+    }
+
+    let app = || App::new().register_command(deploy);
+    let env = Environment::new()
+        .set_var("REGION", "eu")
+        .set_current_dir("/srv/app")
+        .set_argv(["tool", "deploy"]);
+
+    app()
+        .run_with(env.clone().set_var("DEPLOY_TOKEN", "secret"))
+        .unwrap();
+    assert!(app().run_with(env).is_err());
+}
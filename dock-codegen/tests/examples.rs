@@ -0,0 +1,46 @@
+use dock::{
+    command::{Command, Example},
+    App,
+};
+
+use dock_codegen::{command, Args};
+
+#[derive(Args)]
+struct DeployArgs {
+    /// Environment to deploy to
+    #[arg(long)]
+    env: String,
+}
+
+#[test]
+fn test_examples() {
+    #[command(
+        example = "tool deploy --env staging",
+        example("tool deploy --env prod", "Deploy to production")
+    )]
+    fn deploy(args: DeployArgs) {
+        let _ = args.env;
+    }
+
+    #[command(example = "tool deploy --region eu")]
+    fn broken(args: DeployArgs) {
+        let _ = args.env;
+    }
+
+    assert_eq!(
+        deploy.examples(),
+        vec![
+            Example::new("tool deploy --env staging"),
+            Example::new("tool deploy --env prod").set_caption("Deploy to production"),
+        ]
+    );
+
+    App::new()
+        .register_command(deploy)
+        .check_examples()
+        .unwrap();
+    assert!(App::new()
+        .register_command(broken)
+        .check_examples()
+        .is_err());
+}
@@ -0,0 +1,140 @@
+#![cfg(unix)]
+
+use std::{
+    ffi::OsStr,
+    os::unix::{ffi::OsStrExt, fs::PermissionsExt},
+    path::PathBuf,
+};
+
+use dock::{context::Environment, error::Error, App};
+
+use dock_codegen::command;
+
+/// A directory holding the external commands of the tool, removed once dropped
+///
+/// Every test writes its own directory, as running an executable that another test is writing fails.
+struct Plugins(PathBuf);
+
+impl Plugins {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("dock-plugins-{}-{name}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("tool-hello");
+        std::fs::write(
+            &path,
+            "#!/bin/sh\necho \"$GREETING $*\"\n[ \"$1\" = fail ] && exit 3\nexit 0\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        // Files that are not executable are not commands
+        std::fs::write(dir.join("tool-notes"), "").unwrap();
+
+        Self(dir)
+    }
+
+    fn env(&self, args: &[&str]) -> Environment {
+        Environment::new()
+            .set_var("PATH", self.0.to_str().unwrap())
+            .set_var("GREETING", "hi")
+            .set_argv([&["tool"], args].concat())
+    }
+}
+
+impl Drop for Plugins {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn app() -> App {
+    #[command]
+    fn build() {}
+
+    App::new()
+        .enable_external_commands()
+        .register_command(build)
+}
+
+#[test]
+fn test_forwarded_arguments() {
+    let plugins = Plugins::new("forward");
+
+    app()
+        .test_with(plugins.env(&["hello", "--loud", "a b"]))
+        .assert_success()
+        .assert_stdout_contains("hi --loud a b\n");
+}
+
+#[test]
+fn test_invalid_unicode_arguments() {
+    let plugins = Plugins::new("invalid-unicode");
+    let invalid = OsStr::from_bytes(b"caf\xe9");
+
+    app()
+        .test_with(
+            plugins
+                .env(&[])
+                .set_argv([OsStr::new("tool"), OsStr::new("hello"), invalid]),
+        )
+        .assert_success()
+        .assert_stdout_contains("hi caf\u{fffd}\n");
+    app()
+        .test_with(
+            plugins
+                .env(&[])
+                .set_argv([OsStr::new("tool"), OsStr::new("build"), invalid]),
+        )
+        .assert_exit_code(2)
+        .assert_stderr_contains("argument 'caf\u{fffd}' is not valid unicode");
+}
+
+#[test]
+fn test_exit_code() {
+    let plugins = Plugins::new("exit-code");
+
+    let run = app().test_with(plugins.env(&["hello", "fail"]));
+    run.assert_exit_code(3)
+        .assert_stderr_contains("error: external command 'hello' exited with code 3");
+    assert_eq!(
+        run.result,
+        Err(Error::External {
+            command: "hello".to_string(),
+            code: 3
+        })
+    );
+}
+
+#[test]
+fn test_help_listing() {
+    let plugins = Plugins::new("help");
+
+    let run = app().test_with(plugins.env(&["help"]));
+    run.assert_stdout_contains("  build\n")
+        .assert_stdout_contains("  hello  (external)\n");
+    assert!(!run.stdout.contains("notes"));
+
+    app()
+        .test_with(plugins.env(&["help", "hello"]))
+        .assert_stdout_contains("hi --help\n");
+}
+
+#[test]
+fn test_not_executable() {
+    let plugins = Plugins::new("not-executable");
+
+    app()
+        .test_with(plugins.env(&["notes"]))
+        .assert_exit_code(2)
+        .assert_stderr_contains("unknown command 'notes'");
+}
+
+#[test]
+fn test_disabled() {
+    let plugins = Plugins::new("disabled");
+
+    App::new()
+        .test_with(plugins.env(&["hello"]))
+        .assert_exit_code(2)
+        .assert_stderr_contains("unknown command 'hello'");
+}
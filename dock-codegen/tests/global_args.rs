@@ -0,0 +1,46 @@
+use dock::{arg::Arg, context::Context, App};
+
+use dock_codegen::{command, Args};
+
+#[derive(Args)]
+struct GlobalOpts {
+    #[arg(short, long, count)]
+    verbose: u8,
+    #[arg(long)]
+    config: Option<String>,
+}
+
+#[test]
+fn test_global_args() {
+    #[command]
+    fn status(ctx: &Context) -> Result<(), String> {
+        let global = ctx.args::<GlobalOpts>().map_err(|err| err.to_string())?;
+
+        assert_eq!(global.verbose, 2);
+        assert_eq!(global.config.as_deref(), Some("dock.toml"));
+        assert_eq!(ctx.matches.value_of("profile"), Some("release"));
+
+        Ok(())
+    }
+
+    let app = || {
+        App::new()
+            .register_global_args::<GlobalOpts>()
+            .register_global_arg(Arg::new("profile").set_long("profile").set_default("dev"))
+            .register_command(status)
+    };
+
+    app()
+        .run_from([
+            "tool",
+            "-v",
+            "--config",
+            "dock.toml",
+            "status",
+            "--verbose",
+            "--profile=release",
+        ])
+        .unwrap();
+
+    assert!(app().run_from(["tool", "--unknown", "status"]).is_err());
+}
@@ -0,0 +1,155 @@
+use std::{cell::RefCell, rc::Rc};
+
+use dock::{
+    context::{Context, Environment},
+    error::Error,
+    middleware::Next,
+    App,
+};
+
+use dock_codegen::command;
+
+type Log = Rc<RefCell<Vec<String>>>;
+
+#[command]
+fn deploy(ctx: &Context) {
+    ctx.success("deployed");
+}
+
+#[command]
+fn fail() -> Result<(), String> {
+    Err("it broke".to_string())
+}
+
+fn app(log: &Log) -> App {
+    let outer = Rc::clone(log);
+    let before = Rc::clone(log);
+    let after = Rc::clone(log);
+
+    App::new()
+        .register_command(deploy)
+        .register_command(fail)
+        .register_middleware(move |ctx: &Context, next: Next<'_>| {
+            outer
+                .borrow_mut()
+                .push(format!("enter {}", ctx.command.name()));
+            let result = next.run(ctx);
+            outer.borrow_mut().push(format!("leave {}", result.is_ok()));
+            result
+        })
+        .register_before_hook(move |ctx| {
+            before.borrow_mut().push("before".to_string());
+            match ctx.env.env_var("TOKEN") {
+                Some(_) => Ok(()),
+                None => Err(Error::Custom("not logged in".to_string())),
+            }
+        })
+        .register_after_hook(move |_| {
+            after.borrow_mut().push("after".to_string());
+            Ok(())
+        })
+}
+
+fn env(args: &[&str]) -> Environment {
+    Environment::new()
+        .set_var("TOKEN", "secret")
+        .set_argv([&["tool"], args].concat())
+}
+
+#[test]
+fn test_middleware_order() {
+    let log = Log::default();
+
+    app(&log)
+        .test_with(env(&["deploy"]))
+        .assert_success()
+        .assert_stdout_contains("success: deployed");
+    assert_eq!(
+        log.take(),
+        ["enter deploy", "before", "after", "leave true"]
+    );
+}
+
+#[test]
+fn test_failing_before_hook() {
+    let log = Log::default();
+
+    let run = app(&log).test(["tool", "deploy"]);
+    run.assert_exit_code(1)
+        .assert_stderr_contains("error: not logged in");
+    assert!(run.stdout.is_empty());
+    assert_eq!(log.take(), ["enter deploy", "before", "leave false"]);
+}
+
+#[test]
+fn test_failing_command() {
+    let log = Log::default();
+
+    app(&log)
+        .test_with(env(&["fail"]))
+        .assert_exit_code(1)
+        .assert_stderr_contains("error: it broke");
+    assert_eq!(log.take(), ["enter fail", "before", "leave false"]);
+}
+
+#[test]
+fn test_error_hook() {
+    let log = Log::default();
+
+    app(&log)
+        .register_error_hook(|ctx, err| {
+            ctx.warn(&format!("recovered from: {err}"));
+            Ok(())
+        })
+        .test_with(env(&["fail"]))
+        .assert_success()
+        .assert_stderr_contains("warning: recovered from: it broke");
+    assert_eq!(log.take(), ["enter fail", "before", "after", "leave true"]);
+}
+
+#[test]
+fn test_error_hook_order() {
+    let log = Log::default();
+    let recover = |ctx: &Context, err: Error| {
+        ctx.warn(&format!("recovered from: {err}"));
+        Ok(())
+    };
+
+    // Registered after the before hook, the error hook only wraps the command
+    app(&log)
+        .register_error_hook(recover)
+        .test(["tool", "deploy"])
+        .assert_exit_code(1)
+        .assert_stderr_contains("error: not logged in");
+
+    // Registered first, it wraps the before hook too
+    App::new()
+        .register_error_hook(recover)
+        .register_before_hook(|_| Err(Error::Custom("not logged in".to_string())))
+        .register_command(deploy)
+        .test(["tool", "deploy"])
+        .assert_success()
+        .assert_stderr_contains("warning: recovered from: not logged in");
+}
+
+#[test]
+fn test_panicking_command() {
+    #[command]
+    fn crash() {
+        panic!("disk is on fire");
+    }
+
+    App::new()
+        .register_command(crash)
+        .test(["tool", "crash"])
+        .assert_exit_code(101)
+        .assert_stderr_contains("error: command 'crash' panicked: disk is on fire");
+}
+
+#[test]
+fn test_help_skips_middleware() {
+    let log = Log::default();
+
+    app(&log).test(["tool", "--help"]).assert_success();
+    assert!(log.take().is_empty());
+}
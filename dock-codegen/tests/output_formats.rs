@@ -0,0 +1,92 @@
+use dock::App;
+use serde::Serialize;
+
+use dock_codegen::command;
+
+#[derive(Serialize)]
+struct Service {
+    name: String,
+    replicas: u32,
+}
+
+#[command(output)]
+fn services() -> Result<Vec<Service>, String> {
+    Ok(vec![
+        Service {
+            name: "api".to_string(),
+            replicas: 3,
+        },
+        Service {
+            name: "queue".to_string(),
+            replicas: 1,
+        },
+    ])
+}
+
+fn app() -> App {
+    App::new()
+        .enable_output_formats()
+        .register_command(services)
+}
+
+#[test]
+fn test_table_by_default() {
+    let run = app().test(["tool", "services"]);
+
+    run.assert_success();
+    assert_eq!(run.stdout, "NAME   REPLICAS\napi    3\nqueue  1\n");
+}
+
+#[test]
+fn test_json_output() {
+    app()
+        .test(["tool", "services", "--output", "json"])
+        .assert_success()
+        .assert_stdout_contains("[\n  {\n    \"name\": \"api\",\n    \"replicas\": 3\n  },");
+}
+
+#[test]
+fn test_ndjson_output() {
+    let run = app().test(["tool", "services", "--output=ndjson"]);
+
+    run.assert_success();
+    assert_eq!(
+        run.stdout,
+        "{\"name\":\"api\",\"replicas\":3}\n{\"name\":\"queue\",\"replicas\":1}\n"
+    );
+}
+
+#[test]
+fn test_yaml_output() {
+    let run = app().test(["tool", "services", "--output", "yaml"]);
+
+    run.assert_success();
+    assert_eq!(
+        run.stdout,
+        "- name: api\n  replicas: 3\n- name: queue\n  replicas: 1\n"
+    );
+}
+
+#[test]
+fn test_format_before_command() {
+    app()
+        .test(["tool", "--format=ndjson", "services"])
+        .assert_success()
+        .assert_stdout_contains("{\"name\":\"api\",\"replicas\":3}\n");
+}
+
+#[test]
+fn test_unknown_format() {
+    app()
+        .test(["tool", "services", "--output", "xml"])
+        .assert_exit_code(2)
+        .assert_stderr_contains("expected table, json, ndjson or yaml");
+}
+
+#[test]
+fn test_formats_disabled() {
+    App::new()
+        .register_command(services)
+        .test(["tool", "services", "--output", "json"])
+        .assert_exit_code(2);
+}
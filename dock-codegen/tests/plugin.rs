@@ -0,0 +1,96 @@
+use std::{
+    env::consts::{DLL_PREFIX, DLL_SUFFIX},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Arc, OnceLock},
+};
+
+use dock::{
+    plugin::{self, Plugin, DOCK_FEATURES},
+    App,
+};
+
+const FIXTURE: &str = "dock-greeter-plugin";
+/// Manifest of the fixture plugin, which is kept out of the workspace so that only these tests build it
+const MANIFEST: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../dock/tests/fixtures/plugin/Cargo.toml"
+);
+
+/// Build the fixture plugin with the features of the Dock linked into the tests, returning the directory holding it
+///
+/// The plugin is built in its own target directory, since the one of the tests is locked while they run.
+fn plugin_dir() -> &'static Path {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+
+    DIR.get_or_init(|| {
+        let target = Path::new(env!("CARGO_TARGET_TMPDIR")).join("plugin-fixture");
+        let features = DOCK_FEATURES
+            .split(',')
+            .filter(|feature| !feature.is_empty())
+            .map(|feature| format!("dock/{feature}"))
+            .collect::<Vec<String>>()
+            .join(",");
+
+        let status = Command::new(env!("CARGO"))
+            .args([
+                "build",
+                "--quiet",
+                "--manifest-path",
+                MANIFEST,
+                "--features",
+            ])
+            .arg(features)
+            .arg("--target-dir")
+            .arg(&target)
+            .status()
+            .unwrap();
+        assert!(status.success(), "failed to build the fixture plugin");
+
+        let file_name = format!("{DLL_PREFIX}{}{DLL_SUFFIX}", FIXTURE.replace('-', "_"));
+        let dir = target.join("plugins");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::copy(target.join("debug").join(&file_name), dir.join(&file_name)).unwrap();
+
+        dir
+    })
+}
+
+#[test]
+fn load_plugin() {
+    let paths = plugin::discover(plugin_dir());
+    assert_eq!(paths.len(), 1);
+
+    let plugin = Arc::new(Plugin::load(&paths[0]).unwrap());
+    assert_eq!(plugin.name, FIXTURE);
+    assert_eq!(plugin.version, "0.2.0");
+
+    let commands = plugin.commands();
+    assert_eq!(commands.len(), 1);
+    assert_eq!(commands[0].name(), "greet");
+    assert_eq!(commands[0].description(), "Greet from a plugin");
+    assert_eq!(
+        commands[0].category().as_deref(),
+        Some("dock-greeter-plugin 0.2.0 - Plugin loaded by the plugin tests of dock")
+    );
+}
+
+#[test]
+fn run_plugin_command() {
+    App::new()
+        .set_plugin_dir(plugin_dir())
+        .test(["tool", "greet"])
+        .assert_success()
+        .assert_stdout_contains("success: hello from a plugin\n");
+}
+
+#[test]
+fn plugin_commands_in_help() {
+    App::new()
+        .set_plugin_dir(plugin_dir())
+        .test(["tool", "--help"])
+        .assert_success()
+        .assert_stdout_contains(
+            "dock-greeter-plugin 0.2.0 - Plugin loaded by the plugin tests of dock:\n  greet  Greet from a plugin\n",
+        );
+}
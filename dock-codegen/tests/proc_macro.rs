@@ -10,6 +10,11 @@ fn test_proc_macro() {
         println!("Printed from test function of proc-macro")
     }
 
+    #[command(category = "Networking")]
+    fn ping() {}
+
     assert_eq!("test", test.name());
+    assert_eq!(None, test.category());
+    assert_eq!(Some("Networking".to_string()), ping.category());
     println!("-> {}", test.description());
 }
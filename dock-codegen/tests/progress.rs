@@ -0,0 +1,46 @@
+use dock::{context::Context, App};
+
+use dock_codegen::command;
+
+#[test]
+fn test_finished_progress() {
+    #[command]
+    fn fetch(ctx: &Context) {
+        let progress = ctx.progress();
+        let packages = progress.bar("Fetching packages", 3);
+        let index = progress.spinner("Updating index");
+
+        for _ in 0..3 {
+            packages.inc(1);
+        }
+        index.finish_with_message("Updated index");
+        packages.finish();
+        ctx.success("fetched 3 packages");
+    }
+
+    App::new()
+        .register_command(fetch)
+        .test(["tool", "fetch"])
+        .assert_success()
+        .assert_stdout_contains("success: fetched 3 packages")
+        .assert_stderr_contains(
+            "Fetching packages: 0/3 (0%)\nUpdating index...\nUpdated index: done\nFetching packages: done\n",
+        );
+}
+
+#[test]
+fn test_aborted_progress() {
+    #[command]
+    fn fetch(ctx: &Context) -> Result<(), String> {
+        let packages = ctx.progress().bar("Fetching packages", 3);
+        packages.inc(1);
+
+        Err("registry unreachable".to_string())
+    }
+
+    App::new()
+        .register_command(fetch)
+        .test(["tool", "fetch"])
+        .assert_exit_code(1)
+        .assert_stderr_contains("Fetching packages: aborted\nerror: registry unreachable");
+}
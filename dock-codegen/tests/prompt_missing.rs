@@ -0,0 +1,61 @@
+use std::io::Write;
+
+use dock::{context::Context, App};
+
+use dock_codegen::{command, Args};
+
+#[derive(Args)]
+struct ScaleArgs {
+    /// Name of the service
+    service: String,
+    /// Number of replicas
+    #[arg(long)]
+    replicas: u16,
+}
+
+fn app() -> App {
+    #[command]
+    fn scale(ctx: &Context, args: ScaleArgs) {
+        let _ = writeln!(ctx.out(), "scaled {} to {}", args.service, args.replicas);
+    }
+
+    App::new()
+        .enable_prompts()
+        .set_prompt_missing(true)
+        .register_command(scale)
+}
+
+#[test]
+fn prompts_until_valid() {
+    let run = app()
+        .set_answers(&["", "many", "3"])
+        .test(["tool", "scale", "api"]);
+
+    run.assert_success()
+        .assert_stdout_contains("scaled api to 3\n")
+        .assert_stderr_contains("? Number of replicas (--replicas) \nerror: a value is required\n")
+        .assert_stderr_contains("error: invalid digit found in string\n");
+}
+
+#[test]
+fn no_input_fails() {
+    let run = app()
+        .set_answers(&["3"])
+        .test(["tool", "scale", "--no-input"]);
+
+    run.assert_exit_code(2)
+        .assert_stderr_contains("missing required argument '<SERVICE>'");
+    assert!(run.stdout.is_empty());
+}
+
+#[test]
+fn passed_values_are_not_prompted() {
+    app()
+        .test(["tool", "scale", "api", "--replicas", "2"])
+        .assert_success()
+        .assert_stdout_contains("scaled api to 2\n");
+    app()
+        .test(["tool", "scale", "api", "--replicas", "lots"])
+        .assert_exit_code(2)
+        .assert_stderr_contains("invalid value 'lots' for 'replicas'");
+}
@@ -0,0 +1,50 @@
+use dock::{context::Context, App};
+
+use dock_codegen::command;
+
+#[command]
+fn init(ctx: &Context) -> dock::error::Result {
+    let prompt = ctx.prompt();
+    let name = prompt.input("Project name")?;
+    let license = prompt.select("License", &["MIT", "Apache-2.0"])?;
+
+    if prompt.confirm("Create the project?", true)? {
+        ctx.success(&format!("created {name} under license #{license}"));
+    }
+    Ok(())
+}
+
+fn app() -> App {
+    App::new().enable_prompts().register_command(init)
+}
+
+#[test]
+fn test_answered_prompts() {
+    app()
+        .set_answers(&["dock", "2", ""])
+        .test(["tool", "init"])
+        .assert_success()
+        .assert_stdout_contains("success: created dock under license #1")
+        .assert_stderr_contains("? Project name dock\n");
+}
+
+#[test]
+fn test_declined_confirmation() {
+    let run = app()
+        .set_answers(&["dock", "1", "n"])
+        .test(["tool", "init"]);
+
+    run.assert_success();
+    assert!(!run.stdout.contains("created"));
+}
+
+#[test]
+fn test_no_input() {
+    app()
+        .set_answers(&["dock"])
+        .test(["tool", "init", "--no-input"])
+        .assert_exit_code(1)
+        .assert_stderr_contains(
+            "error: cannot ask 'Project name' because input is not interactive",
+        );
+}
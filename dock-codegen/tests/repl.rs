@@ -0,0 +1,92 @@
+use dock::{context::Context, App};
+
+use dock_codegen::{command, Args};
+
+#[derive(Args)]
+struct GreetArgs {
+    /// Name of the person to greet
+    name: String,
+}
+
+/// Greet someone
+#[command]
+fn greet(ctx: &Context, args: GreetArgs) {
+    ctx.success(&format!("Hello, {}!", args.name));
+}
+
+fn app() -> App {
+    App::new().register_command(greet)
+}
+
+#[test]
+fn test_quoted_lines() {
+    app()
+        .test_repl("tool", &["greet \"Ada Lovelace\"", "greet 'Grace Hopper'"])
+        .assert_success()
+        .assert_stderr_contains("tool> greet \"Ada Lovelace\"\ntool> greet 'Grace Hopper'\n")
+        .assert_stdout_contains("success: Hello, Ada Lovelace!\nsuccess: Hello, Grace Hopper!\n");
+}
+
+#[test]
+fn test_errors_keep_session() {
+    app()
+        .test_repl("tool", &["wave", "greet 'unbalanced", "greet Ferris"])
+        .assert_success()
+        .assert_stderr_contains("error: unknown command 'wave'")
+        .assert_stderr_contains("error: unbalanced quotes in 'tool greet 'unbalanced'")
+        .assert_stdout_contains("success: Hello, Ferris!\n");
+}
+
+#[test]
+fn test_help() {
+    app()
+        .test_repl("tool", &["help"])
+        .assert_success()
+        .assert_stdout_contains("Commands:\n  greet\n");
+}
+
+#[test]
+fn test_history_and_exit() {
+    let run = app().test_repl(
+        "tool",
+        &["greet Ada", "", "history", "exit", "greet Ferris"],
+    );
+
+    run.assert_success()
+        .assert_stdout_contains("   1  greet Ada\n   2  history\n");
+    assert!(!run.stderr.contains("greet Ferris"));
+}
+
+#[test]
+fn test_program_prompt() {
+    app()
+        .test_repl("deploy", &["greet Ada"])
+        .assert_stderr_contains("deploy> greet Ada\n");
+}
+
+#[test]
+fn test_custom_prompt() {
+    app()
+        .set_repl_prompt("$ ")
+        .test_repl("tool", &["greet Ada"])
+        .assert_stderr_contains("$ greet Ada\n");
+}
+
+#[test]
+fn test_history_file() {
+    let path = std::env::temp_dir().join(format!("dock-repl-history-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    app()
+        .set_repl_history(&path)
+        .test_repl("tool", &["greet Ada", "", "exit"])
+        .assert_success();
+    let run = app()
+        .set_repl_history(&path)
+        .test_repl("tool", &["history"]);
+    let saved = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+
+    run.assert_stdout_contains("   1  greet Ada\n   2  exit\n   3  history\n");
+    assert_eq!(saved.unwrap(), "greet Ada\nexit\nhistory\n");
+}
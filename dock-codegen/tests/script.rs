@@ -0,0 +1,136 @@
+use std::path::PathBuf;
+
+use dock::{context::Context, error::Error, App};
+
+use dock_codegen::{command, Args};
+
+#[derive(Args)]
+struct DeployArgs {
+    /// Environment to deploy to
+    #[arg(long)]
+    env: String,
+}
+
+#[command]
+fn deploy(ctx: &Context, args: DeployArgs) -> dock::error::Result {
+    if args.env == "production" {
+        return Err(Error::Custom("production is frozen".to_string()));
+    }
+    ctx.success(&format!("deployed to {}", args.env));
+    Ok(())
+}
+
+fn app() -> App {
+    App::new().enable_scripts().register_command(deploy)
+}
+
+const RELEASE: &str = "# Release every environment\n\ndeploy --env staging\ndeploy --env production\ndeploy --env 'qa 2'\nrollback\n";
+
+/// A script written to a temporary file, removed once dropped
+struct Script(PathBuf);
+
+impl Script {
+    fn new(name: &str, contents: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("dock-{}-{name}.txt", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        Self(path)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for Script {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn test_successful_script() {
+    let script = Script::new(
+        "ok",
+        "deploy --env staging\n\n# Then QA\ndeploy --env 'qa 2'\n",
+    );
+
+    app()
+        .test(["tool", "--script", script.path()])
+        .assert_success()
+        .assert_stdout_contains("success: deployed to staging\nsuccess: deployed to qa 2\n");
+}
+
+#[test]
+fn test_stop_at_failure() {
+    let script = Script::new("stop", RELEASE);
+
+    let run = app().test(["tool", "--script", script.path()]);
+    run.assert_exit_code(1)
+        .assert_stdout_contains("success: deployed to staging\n")
+        .assert_stderr_contains("error: production is frozen\nerror: script failed at line 4\n");
+    assert!(!run.stdout.contains("qa 2"));
+}
+
+#[test]
+fn test_keep_going_flag() {
+    let script = Script::new("keep-going", RELEASE);
+
+    let run = app().test(["tool", "--script", script.path(), "--keep-going"]);
+    run.assert_exit_code(1)
+        .assert_stdout_contains("success: deployed to qa 2\n")
+        .assert_stderr_contains("error: unknown command 'rollback'")
+        .assert_stderr_contains("error: script failed at lines 4, 6\n");
+    assert_eq!(
+        run.result,
+        Err(Error::Script {
+            lines: vec![4, 6],
+            error: Box::new(Error::Custom("production is frozen".to_string())),
+        })
+    );
+}
+
+#[test]
+fn test_keep_going_setting() {
+    let script = Script::new("set-keep-going", RELEASE);
+
+    app()
+        .set_keep_going(true)
+        .test(["tool", "--script", script.path()])
+        .assert_stdout_contains("success: deployed to qa 2\n")
+        .assert_stderr_contains("error: script failed at lines 4, 6\n");
+}
+
+#[test]
+fn test_script_with_command() {
+    let script = Script::new("with-command", "deploy --env staging\n");
+
+    app()
+        .test(["tool", "--script", script.path(), "deploy", "--env", "qa"])
+        .assert_exit_code(2)
+        .assert_stderr_contains("error: argument '--script' cannot be used with 'deploy'");
+    let run = app().test(["tool", "deploy", "--env", "qa", "--script", script.path()]);
+    run.assert_exit_code(2)
+        .assert_stderr_contains("error: argument '--script' cannot be used with 'deploy'");
+    assert!(run.stdout.is_empty());
+}
+
+#[test]
+fn test_recursive_script() {
+    let script = Script::new("recursive", "");
+    std::fs::write(&script.0, format!("--script '{}'\n", script.path())).unwrap();
+
+    app()
+        .test(["tool", "--script", script.path()])
+        .assert_exit_code(1)
+        .assert_stderr_contains(
+            "error: scripts are nested more than 16 deep, such as a script running itself\n",
+        );
+}
+
+#[test]
+fn test_missing_script() {
+    app()
+        .test(["tool", "--script", "missing.txt"])
+        .assert_exit_code(1)
+        .assert_stderr_contains("error: failed to read script 'missing.txt'");
+}
@@ -0,0 +1,73 @@
+use std::io::Write;
+
+use dock::{arg::Arg, context::Context, App};
+
+use dock_codegen::{command, Args};
+
+#[derive(Args)]
+struct BuildArgs {
+    /// The target to build
+    target: String,
+    /// Print every compiled file
+    #[arg(short, long)]
+    verbose: bool,
+}
+
+fn app() -> App {
+    #[command(description = "Build the project")]
+    fn build(ctx: &Context, args: BuildArgs) -> Result<(), String> {
+        if args.target == "nowhere" {
+            return Err("no such target".to_string());
+        }
+        if args.verbose {
+            writeln!(ctx.out(), "Building {}", args.target).map_err(|err| err.to_string())?;
+        }
+        ctx.warn("no cache found");
+        ctx.success("built");
+        Ok(())
+    }
+
+    App::new()
+        .set_name("tool")
+        .register_global_arg(
+            Arg::new("quiet")
+                .set_short('q')
+                .set_help("Do not print output")
+                .set_takes_value(false),
+        )
+        .register_command(build)
+}
+
+#[test]
+fn test_harness() {
+    let run = app().test(["tool", "build", "wasm", "-v"]);
+    run.assert_success()
+        .assert_exit_code(0)
+        .assert_stdout_contains("Building wasm\nsuccess: built")
+        .assert_stderr_contains("warning: no cache found");
+    assert!(run.context.unwrap().matches.is_present("verbose"));
+
+    let run = app().test(["tool", "-q", "build", "wasm", "-v"]);
+    assert_eq!(run.stdout, "");
+    run.assert_stderr_contains("warning: no cache found");
+
+    app()
+        .test(["tool", "--color", "always", "build", "wasm"])
+        .assert_stdout_contains("\u{1b}[1;32msuccess:");
+
+    app()
+        .test(["tool", "build", "nowhere"])
+        .assert_failure()
+        .assert_exit_code(1)
+        .assert_stderr_contains("error: no such target");
+
+    app()
+        .test(["tool", "build"])
+        .assert_exit_code(2)
+        .assert_stderr_contains("Usage: tool build [OPTIONS] <TARGET>");
+
+    app()
+        .test(["tool", "build", "--help"])
+        .assert_success()
+        .assert_stdout_snapshot("build_help");
+}
@@ -0,0 +1,28 @@
+//! Captures the compiler and features Dock is built with, which plugins must match as commands cross the library
+//! boundary as trait objects
+
+use std::{env, process::Command};
+
+fn main() {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_string())
+        .unwrap_or_default();
+
+    let mut features = env::vars()
+        .filter_map(|(name, _)| {
+            name.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase().replace('_', "-"))
+        })
+        .collect::<Vec<String>>();
+    features.sort();
+
+    println!("cargo:rustc-env=DOCK_RUSTC_VERSION={version}");
+    println!("cargo:rustc-env=DOCK_FEATURES={}", features.join(","));
+    println!("cargo:rerun-if-env-changed=RUSTC");
+    println!("cargo:rerun-if-changed=build.rs");
+}
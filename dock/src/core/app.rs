@@ -2,14 +2,34 @@
 //!
 //! The singleton `App` struct is used to register the commands and run the application.
 
-use std::io::Read;
+use std::{ffi::OsString, io::Write, path::Path, sync::Arc};
 
 use crate::{
-    context::Context,
-    core::{command::Command, config::AppConfig, parser::Input},
+    arg::{Arg, ArgMatches, Args},
+    color::{ColorChoice, Stream, COLOR},
+    context::{Context, Environment},
+    core::{
+        command::{Command, Example},
+        config::AppConfig,
+        parser::{self, Input, Parser},
+    },
+    error::{Error, Result},
+    external::{self, ExternalCommand},
     help::{DefaultHelpStructure, HelpMessage},
+    middleware::{self, Middleware, Next},
+    output::Output,
+    pager,
+    prompt::{self, Answers, Prompter, NO_INPUT},
+    script::{self, KEEP_GOING, SCRIPT},
+    subcommand::{Execute, Subcommand, VariantCommand},
+    terminal,
+    theme::Theme,
+    usage,
 };
 
+/// Identifier of the built-in global help flag
+pub const HELP: &str = "help";
+
 /// Represents a Dock application
 ///
 /// An `App` instance is used to build and run a command line application from start to finish.
@@ -33,13 +53,37 @@ use crate::{
 /// ```
 ///  
 /// The command line application starts when the [`App::run()`] method is called.
+#[derive(Clone)]
 pub struct App {
     /// Configuration of the application
     pub(crate) config: AppConfig,
     /// Commands registered to the application
     pub(crate) commands: Vec<Box<dyn Command>>,
+    /// Behaviour run around every command, outermost first
+    pub(crate) middleware: Vec<Arc<dyn Middleware>>,
     /// Help structure used for help messages
     pub(crate) help: Option<Box<dyn HelpMessage>>,
+    /// Arguments accepted by every command
+    pub(crate) globals: Vec<Arg>,
+    /// When colored output is used
+    pub(crate) color: ColorChoice,
+    /// Styles used for colored help and error messages
+    pub(crate) theme: Theme,
+    /// Order in which command categories are listed in the help message
+    pub(crate) categories: Vec<String>,
+    /// Optional behaviours enabled on the application
+    pub(crate) features: Features,
+    /// Destination of the output written by the application and its commands
+    pub(crate) output: Output,
+    /// Answers given to prompts instead of reading the terminal
+    pub(crate) answers: Option<Answers>,
+    /// Prompt shown before every line of an interactive session, which defaults to the program name
+    pub(crate) repl_prompt: Option<String>,
+    /// File the lines of interactive sessions are saved to and recalled from
+    pub(crate) repl_history: Option<std::path::PathBuf>,
+    /// Directory the plugins are loaded from, until they are loaded
+    #[cfg(feature = "plugins")]
+    pub(crate) plugin_dir: Option<std::path::PathBuf>,
 }
 
 impl Default for App {
@@ -47,7 +91,26 @@ impl Default for App {
         Self {
             config: AppConfig::new(),
             commands: vec![],
+            middleware: vec![],
             help: None,
+            globals: vec![
+                Arg::new(HELP)
+                    .set_short('h')
+                    .set_long("help")
+                    .set_help("Print the help message")
+                    .set_takes_value(false),
+                ColorChoice::arg(),
+            ],
+            color: ColorChoice::default(),
+            theme: Theme::default(),
+            categories: vec![],
+            features: Features::default(),
+            output: Output::new(),
+            answers: None,
+            repl_prompt: None,
+            repl_history: None,
+            #[cfg(feature = "plugins")]
+            plugin_dir: None,
         }
     }
 }
@@ -132,6 +195,230 @@ impl App {
         self
     }
 
+    /// Register a command for every variant of an enum deriving `Subcommand`
+    ///
+    /// Each variant is registered as a separate command whose callback executes the parsed variant.
+    #[must_use]
+    pub fn register_subcommands<T>(mut self) -> Self
+    where
+        T: Subcommand + Execute + 'static,
+    {
+        for command in VariantCommand::<T>::all() {
+            self.commands.push(Box::new(command));
+        }
+        self
+    }
+
+    /// Register a middleware run around every command
+    ///
+    /// Middleware registered first runs first and wraps the ones registered after it.
+    ///
+    /// ```rs,no_run
+    /// App::from_crate().register_middleware(|ctx: &Context, next: Next<'_>| {
+    ///     let start = Instant::now();
+    ///     let result = next.run(ctx);
+    ///     ctx.info(&format!("done in {:?}", start.elapsed()));
+    ///     result
+    /// })
+    /// ```
+    #[must_use]
+    pub fn register_middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Register a hook run before every command, which is skipped with the error of the hook if it fails
+    #[must_use]
+    pub fn register_before_hook(self, hook: impl Fn(&Context) -> Result + 'static) -> Self {
+        self.register_middleware(middleware::before(hook))
+    }
+
+    /// Register a hook run after every command that succeeds, whose error is returned if it fails
+    #[must_use]
+    pub fn register_after_hook(self, hook: impl Fn(&Context) -> Result + 'static) -> Self {
+        self.register_middleware(middleware::after(hook))
+    }
+
+    /// Register a hook run when a command fails, which receives the error and returns the result of the invocation
+    ///
+    /// The hook wraps the middleware registered after it, so it sees their errors along with the ones of the command,
+    /// including commands that panic, but not the errors of the middleware registered before it.
+    ///
+    /// ```rs,no_run
+    /// App::from_crate().register_error_hook(|ctx, err| {
+    ///     telemetry::report(&ctx.command.name(), &err);
+    ///     Err(err)
+    /// })
+    /// ```
+    #[must_use]
+    pub fn register_error_hook(self, hook: impl Fn(&Context, Error) -> Result + 'static) -> Self {
+        self.register_middleware(middleware::on_error(hook))
+    }
+
+    /// Property setter
+    ///
+    /// Sets when colored output is used, which can be overridden with the `--color` global argument
+    #[must_use]
+    pub fn set_color(mut self, color: ColorChoice) -> Self {
+        self.color = color;
+
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the theme used for colored help and error messages
+    #[must_use]
+    pub fn set_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the order in which command categories are listed in the help message
+    ///
+    /// Categories that are not listed follow in the order they first appear, with uncategorized commands first.
+    #[must_use]
+    pub fn set_category_order(mut self, categories: Vec<String>) -> Self {
+        self.categories = categories;
+
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets whether help messages taller than the terminal are piped through `$PAGER`, falling back to `less -R`.
+    /// Paging is skipped when the output is not a terminal or the `DOCK_NO_PAGER` environment variable is set.
+    #[must_use]
+    pub fn set_pager(mut self, pager: bool) -> Self {
+        self.features.set(Feature::Pager, pager);
+
+        self
+    }
+
+    /// Register a global argument to the dock application
+    ///
+    /// Global arguments are accepted before or after the command name and their values are available to every command through the `Context`.
+    #[must_use]
+    pub fn register_global_arg(mut self, arg: Arg) -> Self {
+        self.globals.push(arg);
+        self
+    }
+
+    /// Register every argument of a group deriving `Args` as a global argument
+    ///
+    /// Commands can then take the group as a parameter or construct it with [`Context::args`].
+    #[must_use]
+    pub fn register_global_args<T: Args>(mut self) -> Self {
+        self.globals.extend(T::spec());
+        self
+    }
+
+    /// Enable the built-in output formats
+    ///
+    /// Registers the `--output <FORMAT>` global argument, also accepted as `--format`, which chooses whether values
+    /// rendered by commands are printed as a table, JSON, NDJSON or YAML.
+    #[cfg(feature = "formats")]
+    #[must_use]
+    pub fn enable_output_formats(mut self) -> Self {
+        self.globals.push(crate::format::Format::arg());
+        self.features.set(Feature::Formats, true);
+        self
+    }
+
+    /// Enable the built-in prompt flags
+    ///
+    /// Registers the `-y/--yes` global flag, which answers confirmations without asking, and the `--no-input` global
+    /// flag, which makes prompts fail instead of waiting for input.
+    #[must_use]
+    pub fn enable_prompts(mut self) -> Self {
+        self.globals.extend(prompt::args());
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets whether required arguments that were not passed are prompted for when the session is interactive,
+    /// using the help text of the argument as the question and its validator to check the answer.
+    /// Non-interactive sessions and `--no-input` still fail with a missing argument error.
+    #[must_use]
+    pub fn set_prompt_missing(mut self, prompt_missing: bool) -> Self {
+        self.features.set(Feature::PromptMissing, prompt_missing);
+        self
+    }
+
+    /// Resolve unknown commands to executables found on `PATH`
+    ///
+    /// An unknown command such as `tool deploy` runs the `tool-deploy` executable, forwarding the arguments following
+    /// the command name and the environment. Such executables are listed in the help message as external commands.
+    #[must_use]
+    pub fn enable_external_commands(mut self) -> Self {
+        self.features.set(Feature::External, true);
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the directory the plugins are loaded from when the application starts, registering the commands they
+    /// export after those registered to the application. Plugins that cannot be loaded are skipped with a warning.
+    #[cfg(feature = "plugins")]
+    #[must_use]
+    pub fn set_plugin_dir(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.plugin_dir = Some(dir.into());
+        self
+    }
+
+    /// Enable the built-in script flags
+    ///
+    /// Registers the `--script <FILE>` global argument, which runs the commands listed in the file one per line,
+    /// and the `--keep-going` global flag, which keeps running the script after a command fails.
+    #[must_use]
+    pub fn enable_scripts(mut self) -> Self {
+        self.globals.extend(script::args());
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets whether a script keeps running after a command fails, as if `--keep-going` was passed
+    #[must_use]
+    pub fn set_keep_going(mut self, keep_going: bool) -> Self {
+        self.features.set(Feature::KeepGoing, keep_going);
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the prompt shown before every line read by [`App::repl`], such as `"dock> "`
+    #[must_use]
+    pub fn set_repl_prompt(mut self, prompt: &str) -> Self {
+        self.repl_prompt = Some(prompt.to_string());
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the file the lines entered in [`App::repl`] are appended to, so that they are recalled in later sessions
+    #[must_use]
+    pub fn set_repl_history(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.repl_history = Some(path.into());
+        self
+    }
+
+    /// Enable the built-in logging integration
+    ///
+    /// Registers the `-v/--verbose` and `-q/--quiet` global flags and installs a logger for the `log` facade
+    /// with the matching level before a command is invoked.
+    #[cfg(feature = "logging")]
+    #[must_use]
+    pub fn enable_logging(mut self) -> Self {
+        self.globals.extend(crate::logging::args());
+        self.features.set(Feature::Logging, true);
+        self
+    }
+
     /// Property setter
     ///
     /// Set the help command struct that is used for generating help commands
@@ -141,35 +428,568 @@ impl App {
         self
     }
     #[must_use]
-    fn construct_context(self, command: impl Command + 'static) -> Context {
-        Context::new(Box::new(command), self)
+    fn construct_context(
+        self,
+        command: Box<dyn Command>,
+        matches: ArgMatches,
+        env: Environment,
+    ) -> Context {
+        Context::new(command, matches, self).set_env(env)
     }
 
     #[must_use]
-    fn generate_default_help(&self) -> Box<dyn HelpMessage> {
-        Box::new(DefaultHelpStructure::new(
-            self.config.clone(),
-            self.commands.clone(),
-        ))
+    fn generate_default_help(&self, program: &str, env: &Environment) -> Box<dyn HelpMessage> {
+        let mut commands = self.commands.clone();
+        if self.features.contains(Feature::External) {
+            for command in external::discover(program, env.env_var("PATH")) {
+                if !self
+                    .commands
+                    .iter()
+                    .any(|known| known.name() == command.name)
+                {
+                    commands.push(Box::new(command));
+                }
+            }
+        }
+
+        Box::new(
+            DefaultHelpStructure::new(self.config.clone(), commands)
+                .set_theme(self.theme)
+                .set_category_order(self.categories.clone())
+                .set_program(program)
+                .set_global_args(self.globals.clone())
+                .set_width(terminal::width(env, &self.output)),
+        )
     }
 
     #[must_use]
-    fn get_help_structure(&self) -> Box<dyn HelpMessage> {
+    fn get_help_structure(&self, program: &str, env: &Environment) -> Box<dyn HelpMessage> {
         if let Some(help) = &self.help {
             dyn_clone::clone_box(&**help)
         } else {
-            self.generate_default_help()
+            self.generate_default_help(program, env)
+        }
+    }
+
+    /// Load the plugins of the plugin directory and register their commands, warning about those that cannot be loaded
+    ///
+    /// The directory is forgotten once loaded, so that interactive sessions and scripts load the plugins only once.
+    #[cfg(feature = "plugins")]
+    pub(crate) fn with_plugins(mut self, report: &Report) -> Self {
+        use crate::plugin::{self, Plugin};
+
+        let Some(dir) = self.plugin_dir.take() else {
+            return self;
+        };
+
+        for path in plugin::discover(&dir) {
+            match Plugin::load(&path) {
+                Ok(plugin) => self.commands.extend(std::sync::Arc::new(plugin).commands()),
+                Err(err) => report.print_warning(&format!("skipped plugin: {err}")),
+            }
+        }
+
+        self
+    }
+
+    /// Find the executable run by an unknown command, if external commands are enabled
+    fn find_external(
+        &self,
+        program: &str,
+        name: &str,
+        env: &Environment,
+    ) -> Option<ExternalCommand> {
+        if self.features.contains(Feature::External) {
+            external::find(program, name, env.env_var("PATH"))
+        } else {
+            None
         }
     }
 
-    ///Start the Dock application
+    /// Find an enabled command by its name
+    #[must_use]
+    fn find_command(&self, name: &str) -> Option<Box<dyn Command>> {
+        self.commands
+            .iter()
+            .find(|command| !command.disabled() && command.name() == name)
+            .cloned()
+    }
+
+    /// The program name shown in help messages, which is the application name if one is set
+    pub(crate) fn program_name(&self, argv0: Option<&str>) -> String {
+        self.config.name.clone().unwrap_or_else(|| {
+            argv0
+                .map(Path::new)
+                .and_then(Path::file_stem)
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+    }
+
+    /// The program name shown in help messages, taken from the argv of the environment unless the application name
+    /// is set
+    pub(crate) fn program_from(&self, env: &Environment) -> String {
+        let argv0 = env
+            .argv
+            .first()
+            .map(|arg| arg.to_string_lossy().to_string());
+        self.program_name(argv0.as_deref())
+    }
+
+    /// Print the help message of the application, or of a single command if one is given
+    fn print_help(&self, program: &str, command: Option<&dyn Command>, report: &Report) {
+        let help = self.get_help_structure(program, &report.env);
+        let colored = report.should_color(Stream::Stdout);
+
+        let message = match command {
+            Some(command) if colored => help.get_command_help_colored(command),
+            Some(command) => help.get_command_help(command),
+            None if colored => help.get_help_colored(),
+            None => help.get_help(),
+        };
+
+        if self.features.contains(Feature::Pager) {
+            pager::page(&message, &report.env, &self.output);
+        } else {
+            let _ = writeln!(self.output.writer(Stream::Stdout), "{message}");
+        }
+    }
+
+    fn format_error(err: &Error, report: &Report) -> String {
+        let theme = report.should_color(Stream::Stderr).then_some(&report.theme);
+
+        let mut message = match theme {
+            Some(theme) => format!("{} {err}", theme.error.paint("error:")),
+            None => format!("error: {err}"),
+        };
+
+        if err.is_usage() {
+            let usage = match theme {
+                Some(theme) => {
+                    format!("{} {}", theme.heading.paint("Usage:"), report.usage_colored)
+                }
+                None => format!("Usage: {}", report.usage),
+            };
+            message = format!("{message}\n\n{usage}\n\nFor more information, try '--help'.");
+        }
+
+        message
+    }
+
+    /// Start the Dock application
+    ///
+    /// The arguments passed to the process are parsed and the matching command is invoked.
+    /// If parsing or the command fails, the process exits with the exit code of the error.
     pub fn run(self) {
-        let mut buf = String::new();
-        std::io::stdin()
-            .read_to_string(&mut buf)
-            .expect("Failed to read standard input stream");
+        if let Err(err) = self.run_with(Environment::default()) {
+            std::process::exit(err.exit_code());
+        }
+    }
+
+    /// Run the application with the given arguments, the first of which is the program name
+    ///
+    /// The help message is printed when no command, the `help` command or a `-h`/`--help` flag is passed.
+    /// Errors are printed to the standard error stream along with the relevant usage line before being returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command is unknown, the arguments do not match its spec or the command fails.
+    pub fn run_from<I, T>(self, args: I) -> Result
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        let args = args.into_iter().map(Into::<String>::into);
+        self.run_with(Environment::default().set_argv(args))
+    }
+
+    /// Run the application in the given environment, whose argv holds the arguments to parse
+    ///
+    /// Environment variables read by arguments and color detection come from the environment rather than the
+    /// process, and the invoked command receives it through `ctx.env`, which makes invocations deterministic in tests.
+    ///
+    /// ```rs,no_run
+    /// app().run_with(
+    ///     Environment::new()
+    ///         .set_var("DEPLOY_TOKEN", "secret")
+    ///         .set_argv(["tool", "deploy"]),
+    /// )
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command is unknown, the arguments do not match its spec or the command fails.
+    pub fn run_with(self, env: Environment) -> Result {
+        self.execute(env).0
+    }
+
+    /// Run the application in the given environment and return the context the command was invoked with
+    ///
+    /// No context is returned if the help message was printed or the arguments could not be parsed.
+    pub(crate) fn execute(self, env: Environment) -> (Result, Option<Context>) {
+        let input = Input::from_args(&env.argv);
+        self.execute_input(&input, env)
+    }
+
+    /// Run the application with the input rather than the argv of the environment
+    pub(crate) fn execute_input(
+        self,
+        input: &Input,
+        env: Environment,
+    ) -> (Result, Option<Context>) {
+        let program = self.program_name(input.program());
+        let mut report = self.report(&program, env);
+        #[cfg(feature = "plugins")]
+        let app = self.with_plugins(&report);
+        #[cfg(not(feature = "plugins"))]
+        let app = self;
+
+        let (result, context) = match app.dispatch(&program, input, &mut report) {
+            Ok(Some(ctx)) => (
+                Next::new(&ctx.app.middleware, &*ctx.command).run(&ctx),
+                Some(ctx),
+            ),
+            Ok(None) => (Ok(()), None),
+            Err(err) => (Err(err), None),
+        };
+
+        if let Err(err) = &result {
+            report.print_error(err);
+        }
+
+        (result, context)
+    }
+
+    /// Run a line entered in an interactive session or read from a script, such as `build --release`
+    pub(crate) fn execute_line(self, program: &str, line: &str, env: Environment) -> Result {
+        match parser::quote(program).and_then(|program| Input::new(&format!("{program} {line}"))) {
+            Ok(input) => self.execute_input(&input, env).0,
+            Err(err) => {
+                self.report(program, env).print_error(&err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Create the state used to report errors before a command is found
+    pub(crate) fn report(&self, program: &str, env: Environment) -> Report {
+        Report {
+            color: self.color,
+            theme: self.theme,
+            usage: usage::app_usage(program, &self.globals, None),
+            usage_colored: usage::app_usage(program, &self.globals, Some(&self.theme)),
+            env,
+            output: self.output.clone(),
+        }
+    }
+
+    /// Parse the examples of every command against its argument spec and the global arguments
+    ///
+    /// This is meant to be called from a test so that the examples shown in help messages stay valid.
+    ///
+    /// ```rs,no_run
+    /// #[test]
+    /// fn examples() {
+    ///     app().check_examples().unwrap();
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first example that does not invoke its command with valid arguments.
+    pub fn check_examples(&self) -> Result {
+        for command in &self.commands {
+            for example in command.examples() {
+                self.check_example(&**command, &example).map_err(|err| {
+                    Error::Custom(format!("invalid example '{}': {err}", example.command))
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_example(&self, command: &dyn Command, example: &Example) -> Result {
+        let split = shlex::split(&example.command)
+            .ok_or_else(|| Error::Custom("unbalanced quotes".to_string()))?;
+        let args = split.get(1..).unwrap_or_default();
+        let mut matches = ArgMatches::new();
+
+        match Parser::new(&self.globals).parse_command(args, &mut matches)? {
+            Some((name, args)) if name == command.name() => {
+                let spec = [command.args(), self.globals.clone()].concat();
+                let parser = Parser::new(&spec);
+                parser.parse_into(args, &mut matches)?;
+
+                if matches.is_present(HELP) {
+                    Ok(())
+                } else {
+                    parser.resolve(&mut matches, &Environment::new())
+                }
+            }
+            _ => Err(Error::Custom(format!(
+                "expected an invocation of '{}'",
+                command.name()
+            ))),
+        }
+    }
+
+    /// Prompt for the required arguments still absent after applying environment variables and defaults
+    fn prompt_missing_args(
+        &self,
+        parser: &Parser<'_>,
+        matches: &mut ArgMatches,
+        report: &Report,
+    ) -> Result {
+        parser.apply_defaults(matches, &report.env);
+
+        let prompter = Prompter::new(self.output.clone(), self.answers.clone())
+            .set_theme(report.should_color(Stream::Stderr).then_some(self.theme));
+        if !prompter.is_interactive() || matches.is_present(NO_INPUT) {
+            return Ok(());
+        }
+
+        for arg in parser.missing(matches) {
+            let message = if arg.help.is_empty() {
+                arg.display_name()
+            } else {
+                format!("{} ({})", arg.help, arg.display_name())
+            };
+
+            if arg.takes_value {
+                let value = prompter.input_with(&message, |value| match arg.validator {
+                    _ if value.is_empty() => Err("a value is required".to_string()),
+                    Some(validator) => validator(value),
+                    None => Ok(()),
+                })?;
+                matches.add_value(&arg.id, &value);
+            } else if prompter.confirm(&message, false)? {
+                matches.add_occurrence(&arg.id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create the context of an external command, which receives its arguments unparsed
+    fn external_context(
+        self,
+        command: ExternalCommand,
+        matches: ArgMatches,
+        report: &Report,
+    ) -> Context {
+        let mut ctx = self.construct_context(Box::new(command), matches, report.env.clone());
+        ctx.color = report.color;
+        ctx
+    }
+
+    /// Parse the arguments and invoke the matching command
+    ///
+    /// The report is updated with the color choice passed through the `--color` global argument and
+    /// the usage line of the command once it is found.
+    fn dispatch(
+        self,
+        program: &str,
+        input: &Input,
+        report: &mut Report,
+    ) -> Result<Option<Context>> {
+        let mut matches = ArgMatches::new();
+        let args = input.args();
+
+        let found = Parser::new(&self.globals).parse_command(args, &mut matches);
+        report.color = matches.get(COLOR)?.unwrap_or(self.color);
+        let found = found?;
+
+        // Only the arguments following the name of an external command are passed on without being parsed
+        let (leading, forwarded) = input
+            .os_args()
+            .split_at(args.len() - found.map_or(0, |(_, rest)| rest.len()));
+        valid_unicode(leading)?;
+
+        if let Some(path) = matches.value_of(SCRIPT) {
+            if let Some((name, _)) = found {
+                return Err(script::conflict(name));
+            }
+            let keep_going =
+                self.features.contains(Feature::KeepGoing) || matches.is_present(KEEP_GOING);
+            self.script_file(path, &report.env, keep_going)?;
+            return Ok(None);
+        }
+
+        let (name, args) = match found {
+            Some(("help", [name, ..])) => {
+                if let Some(command) = self.find_command(name) {
+                    self.print_help(program, Some(&*command), report);
+                    return Ok(None);
+                }
+
+                let command = self
+                    .find_external(program, name, &report.env)
+                    .ok_or_else(|| Error::UnknownCommand(name.clone()))?;
+                return Ok(Some(self.external_context(
+                    command.set_args(["--help"]),
+                    matches,
+                    report,
+                )));
+            }
+            Some(("help", _)) | None => {
+                self.print_help(program, None, report);
+                return Ok(None);
+            }
+            Some(found) => found,
+        };
+
+        let Some(command) = self.find_command(name) else {
+            let command = self
+                .find_external(program, name, &report.env)
+                .ok_or_else(|| Error::UnknownCommand(name.to_string()))?;
+            return Ok(Some(self.external_context(
+                command.set_args(forwarded),
+                matches,
+                report,
+            )));
+        };
+        valid_unicode(forwarded)?;
+        report.usage = command.usage(program);
+        report.usage_colored = command.usage_colored(program, &self.theme);
+
+        let spec = [command.args(), self.globals.clone()].concat();
+        let parser = Parser::new(&spec);
+
+        let parsed = parser.parse_into(args, &mut matches);
+        report.color = matches.get(COLOR)?.unwrap_or(self.color);
+        parsed?;
+
+        if matches.is_present(SCRIPT) {
+            return Err(script::conflict(name));
+        }
+
+        if matches.is_present(HELP) {
+            self.print_help(program, Some(&*command), report);
+            return Ok(None);
+        }
+
+        if self.features.contains(Feature::PromptMissing) {
+            self.prompt_missing_args(&parser, &mut matches, report)?;
+        }
+        parser.resolve(&mut matches, &report.env)?;
+
+        #[cfg(feature = "formats")]
+        if self.features.contains(Feature::Formats) {
+            matches.get::<crate::format::Format>(crate::format::OUTPUT)?;
+        }
+
+        #[cfg(feature = "logging")]
+        if self.features.contains(Feature::Logging) {
+            crate::logging::DockLogger::init(
+                &matches,
+                report.should_color(Stream::Stderr).then_some(self.theme),
+                &self.output,
+            );
+        }
+
+        let mut ctx = self.construct_context(command, matches, report.env.clone());
+        ctx.color = report.color;
+
+        Ok(Some(ctx))
+    }
+}
 
-        let _command = Input::new(&buf);
+/// Fail on the first argument that is not valid unicode, which only external commands are passed
+fn valid_unicode(args: &[OsString]) -> Result {
+    match args.iter().find(|arg| arg.to_str().is_none()) {
+        Some(arg) => Err(Error::InvalidUnicode(arg.to_string_lossy().to_string())),
+        None => Ok(()),
+    }
+}
+
+/// An optional behaviour of the application
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Feature {
+    /// Help messages taller than the terminal are piped through a pager
+    Pager,
+    /// The `--output` global argument choosing the format of rendered values is registered
+    #[cfg(feature = "formats")]
+    Formats,
+    /// Absent required arguments are prompted for in interactive sessions
+    PromptMissing,
+    /// A script keeps running after a command fails
+    KeepGoing,
+    /// Unknown commands run the matching executables found on `PATH`
+    External,
+    /// The logger is installed before a command is invoked
+    #[cfg(feature = "logging")]
+    Logging,
+}
+
+/// The set of optional behaviours enabled on the application
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Features(u8);
+
+impl Features {
+    /// Whether the feature is enabled
+    pub(crate) fn contains(self, feature: Feature) -> bool {
+        self.0 & Self::bit(feature) != 0
+    }
+
+    /// Enable or disable the feature
+    pub(crate) fn set(&mut self, feature: Feature, enabled: bool) {
+        if enabled {
+            self.0 |= Self::bit(feature);
+        } else {
+            self.0 &= !Self::bit(feature);
+        }
+    }
+
+    fn bit(feature: Feature) -> u8 {
+        1 << feature as u8
+    }
+}
+
+/// State gathered while dispatching which is used to report errors
+pub(crate) struct Report {
+    /// When colored output is used, after applying the `--color` global argument
+    color: ColorChoice,
+    /// Styles used for colored error messages
+    theme: Theme,
+    /// Usage line of the invoked command, or of the application if no command was found
+    usage: String,
+    /// Colored version of the usage line
+    usage_colored: String,
+    /// Environment the application is run in
+    env: Environment,
+    /// Destination errors are written to
+    output: Output,
+}
+
+impl Report {
+    /// Whether output written to the stream should be colored, given the color choice, environment and output
+    fn should_color(&self, stream: Stream) -> bool {
+        self.color.resolve(
+            |name| self.env.env_var(name).map(str::to_string),
+            || self.output.is_terminal(stream),
+        )
+    }
+
+    /// Print the warning to the standard error stream
+    #[cfg(feature = "plugins")]
+    pub(crate) fn print_warning(&self, message: &str) {
+        let label = if self.should_color(Stream::Stderr) {
+            self.theme.warning.paint("warning:").to_string()
+        } else {
+            "warning:".to_string()
+        };
+
+        let _ = writeln!(self.output.writer(Stream::Stderr), "{label} {message}");
+    }
+
+    /// Print the error to the standard error stream, along with the usage line if it was caused by invalid usage
+    pub(crate) fn print_error(&self, err: &Error) {
+        let _ = writeln!(
+            self.output.writer(Stream::Stderr),
+            "{}",
+            App::format_error(err, self)
+        );
     }
 }
 
@@ -228,4 +1048,45 @@ mod app_tests {
         );
         assert_eq!(app.config.version.as_ref().unwrap(), &"0.1.0".to_string());
     }
+
+    #[test]
+    fn color_argument() {
+        assert!(App::new().run_from(["dock", "--color", "never"]).is_ok());
+        assert_eq!(
+            App::new().run_from(["dock", "--color=sometimes"]),
+            Err(Error::InvalidValue {
+                arg: COLOR.to_string(),
+                value: "sometimes".to_string(),
+                reason: "expected auto, always or never".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn feature_set() {
+        let mut features = Features::default();
+        features.set(Feature::PromptMissing, true);
+        assert!(features.contains(Feature::PromptMissing));
+
+        features.set(Feature::PromptMissing, false);
+        assert!(!features.contains(Feature::PromptMissing));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn invalid_unicode() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let mut app = App::new();
+        app.output = Output::captured();
+        let result = app.run_with(
+            Environment::new().set_argv([OsStr::new("dock"), OsStr::from_bytes(b"caf\xe9")]),
+        );
+
+        assert_eq!(
+            result,
+            Err(Error::InvalidUnicode("caf\u{fffd}".to_string()))
+        );
+        assert_eq!(result.unwrap_err().exit_code(), 2);
+    }
 }
@@ -0,0 +1,398 @@
+//! The part of the application that handles argument specs and parsed argument values
+
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{
+    context::Context,
+    error::{Error, Result},
+};
+
+/// Checks a raw value passed to an argument, returning the reason it is invalid
+pub type Validator = fn(&str) -> std::result::Result<(), String>;
+
+/// Represents the spec of a single argument accepted by a command
+///
+/// Arguments with neither a short nor a long name are positional and are matched in the order they are declared.
+///
+/// ```rs,no_run
+/// Arg::new("target")
+///     .set_short('t')
+///     .set_long("target")
+///     .set_help("The target to build")
+///     .set_takes_value(true)
+/// ```
+#[derive(Debug, Clone)]
+pub struct Arg {
+    /// Identifier used to look up the parsed value
+    pub id: String,
+    /// Short name, matched as `-c`
+    pub short: Option<char>,
+    /// Long name, matched as `--name`
+    pub long: Option<String>,
+    /// Alternative long names, matched like the long name but not shown in help messages
+    pub aliases: Vec<String>,
+    /// Help text shown in help messages
+    pub help: String,
+    /// Name of the value shown in help messages
+    pub value_name: Option<String>,
+    /// Value used when the argument is not passed
+    pub default: Option<String>,
+    /// Environment variable read when the argument is not passed
+    pub env: Option<String>,
+    /// Whether the argument takes a value or is a flag
+    pub takes_value: bool,
+    /// Whether the argument must be passed
+    pub required: bool,
+    /// Whether the argument can hold multiple values
+    pub multiple: bool,
+    /// Check run on every value of the argument once parsed
+    pub validator: Option<Validator>,
+}
+
+// Validators are left out, as function pointers do not compare reliably
+impl PartialEq for Arg {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.short == other.short
+            && self.long == other.long
+            && self.aliases == other.aliases
+            && self.help == other.help
+            && self.value_name == other.value_name
+            && self.default == other.default
+            && self.env == other.env
+            && self.takes_value == other.takes_value
+            && self.required == other.required
+            && self.multiple == other.multiple
+    }
+}
+
+impl Eq for Arg {}
+
+impl Arg {
+    /// Create a new argument spec with the given identifier
+    ///
+    /// The argument is an optional positional value until configured otherwise.
+    #[must_use]
+    pub fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            short: None,
+            long: None,
+            aliases: vec![],
+            help: String::new(),
+            value_name: None,
+            default: None,
+            env: None,
+            takes_value: true,
+            required: false,
+            multiple: false,
+            validator: None,
+        }
+    }
+
+    /// Property setter
+    ///
+    /// Sets the short name of the argument
+    #[must_use]
+    pub fn set_short(mut self, short: char) -> Self {
+        self.short = Some(short);
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the long name of the argument
+    #[must_use]
+    pub fn set_long(mut self, long: &str) -> Self {
+        self.long = Some(long.to_string());
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Adds an alternative long name to the argument
+    #[must_use]
+    pub fn set_alias(mut self, alias: &str) -> Self {
+        self.aliases.push(alias.to_string());
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the help text of the argument
+    #[must_use]
+    pub fn set_help(mut self, help: &str) -> Self {
+        self.help = help.to_string();
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the value name shown in help messages
+    #[must_use]
+    pub fn set_value_name(mut self, value_name: &str) -> Self {
+        self.value_name = Some(value_name.to_string());
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the default value of the argument
+    #[must_use]
+    pub fn set_default(mut self, default: &str) -> Self {
+        self.default = Some(default.to_string());
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the environment variable the value is read from when the argument is not passed
+    #[must_use]
+    pub fn set_env(mut self, env: &str) -> Self {
+        self.env = Some(env.to_string());
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets whether the argument takes a value or is a flag
+    #[must_use]
+    pub fn set_takes_value(mut self, takes_value: bool) -> Self {
+        self.takes_value = takes_value;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets whether the argument must be passed
+    #[must_use]
+    pub fn set_required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets whether the argument can hold multiple values
+    #[must_use]
+    pub fn set_multiple(mut self, multiple: bool) -> Self {
+        self.multiple = multiple;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the check run on every value of the argument, such as converting it into the type of a field
+    ///
+    /// ```rs,no_run
+    /// Arg::new("jobs").set_validator(|value| {
+    ///     value.parse::<u8>().map(|_| ()).map_err(|err| err.to_string())
+    /// })
+    /// ```
+    #[must_use]
+    pub fn set_validator(mut self, validator: Validator) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    /// Whether the argument is matched by position rather than by name
+    #[must_use]
+    pub fn is_positional(&self) -> bool {
+        self.short.is_none() && self.long.is_none()
+    }
+
+    /// The name used to refer to the argument in messages
+    #[must_use]
+    pub fn display_name(&self) -> String {
+        match (&self.long, self.short) {
+            (Some(long), _) => format!("--{long}"),
+            (None, Some(short)) => format!("-{short}"),
+            (None, None) => format!(
+                "<{}>",
+                self.value_name
+                    .clone()
+                    .unwrap_or_else(|| self.id.to_uppercase())
+            ),
+        }
+    }
+}
+
+/// Holds the values parsed for the arguments of a command
+///
+/// Values are stored as raw strings keyed by the argument identifier and converted on access.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArgMatches {
+    values: HashMap<String, Vec<String>>,
+    occurrences: HashMap<String, usize>,
+}
+
+impl ArgMatches {
+    /// Construct an empty set of matches
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an occurrence of a flag
+    pub fn add_occurrence(&mut self, id: &str) {
+        *self.occurrences.entry(id.to_string()).or_default() += 1;
+    }
+
+    /// Record a value of an argument
+    pub fn add_value(&mut self, id: &str, value: &str) {
+        self.add_occurrence(id);
+        self.values
+            .entry(id.to_string())
+            .or_default()
+            .push(value.to_string());
+    }
+
+    /// Whether the argument was passed or resolved from its environment variable or default
+    #[must_use]
+    pub fn is_present(&self, id: &str) -> bool {
+        self.occurrences(id) > 0
+    }
+
+    /// The number of times the argument was passed
+    #[must_use]
+    pub fn occurrences(&self, id: &str) -> usize {
+        self.occurrences.get(id).copied().unwrap_or_default()
+    }
+
+    /// The last raw value of the argument
+    #[must_use]
+    pub fn value_of(&self, id: &str) -> Option<&str> {
+        self.values
+            .get(id)
+            .and_then(|values| values.last())
+            .map(String::as_str)
+    }
+
+    /// All the raw values of the argument
+    #[must_use]
+    pub fn values_of(&self, id: &str) -> &[String] {
+        self.values.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Get the value of the argument converted into `T`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] if the value cannot be converted.
+    pub fn get<T>(&self, id: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.value_of(id)
+            .map(|value| convert(id, value))
+            .transpose()
+    }
+
+    /// Get the value of an argument that must be present converted into `T`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::MissingArgument`] if the argument is absent or [`Error::InvalidValue`] if the value cannot be converted.
+    pub fn get_required<T>(&self, id: &str) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.get(id)?
+            .ok_or_else(|| Error::MissingArgument(id.to_string()))
+    }
+
+    /// Get all the values of the argument converted into `T`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] if any of the values cannot be converted.
+    pub fn get_many<T>(&self, id: &str) -> Result<Vec<T>>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.values_of(id)
+            .iter()
+            .map(|value| convert(id, value))
+            .collect()
+    }
+}
+
+fn convert<T>(id: &str, value: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    value.parse().map_err(|err: T::Err| Error::InvalidValue {
+        arg: id.to_string(),
+        value: value.to_string(),
+        reason: err.to_string(),
+    })
+}
+
+/// The trait implemented by groups of arguments, usually through `#[derive(Args)]`.
+///
+/// A type implementing `Args` can be taken as a parameter by a `command` callback,
+/// which makes its spec part of the command and parses it from the `Context` on invocation.
+///
+/// ```rs,no_run
+/// #[derive(Args)]
+/// struct BuildArgs {
+///     /// The target to build
+///     #[arg(short, long, default = "debug")]
+///     target: String,
+/// }
+///
+/// #[command]
+/// fn build(args: BuildArgs) {
+///     // --snip--
+/// }
+/// ```
+pub trait Args: Sized {
+    /// Get the spec of the arguments in the group
+    fn spec() -> Vec<Arg>;
+
+    /// Construct the group from parsed matches
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a required value is missing or a value cannot be converted.
+    fn from_matches(matches: &ArgMatches) -> Result<Self>;
+
+    /// Construct the group from the context of a command invocation
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a required value is missing or a value cannot be converted.
+    fn from_context(ctx: &Context) -> Result<Self> {
+        Self::from_matches(&ctx.matches)
+    }
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use super::*;
+
+    #[test]
+    fn typed_access() {
+        let mut matches = ArgMatches::new();
+        matches.add_value("jobs", "4");
+        matches.add_value("file", "a.rs");
+        matches.add_value("file", "b.rs");
+
+        assert_eq!(matches.get::<u8>("jobs").unwrap(), Some(4));
+        assert_eq!(matches.get::<u8>("missing").unwrap(), None);
+        assert_eq!(
+            matches.get_many::<String>("file").unwrap(),
+            vec!["a.rs".to_string(), "b.rs".to_string()]
+        );
+        assert!(matches.get::<bool>("jobs").is_err());
+        assert_eq!(
+            matches.get_required::<u8>("missing"),
+            Err(Error::MissingArgument("missing".to_string()))
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Part of the Dock application that decides whether output is colored
+//!
+//! The choice can be set on the `App` builder and overridden by the built-in `--color <WHEN>` global argument.
+//! When the choice is `auto`, the `NO_COLOR`, `CLICOLOR_FORCE` and `TERM` environment variables are honoured and
+//! colors are only used if the stream is a terminal.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::{
+    fmt::{self, Display},
+    io::IsTerminal,
+    str::FromStr,
+};
+
+use crate::arg::Arg;
+
+/// Identifier of the built-in global color argument
+pub const COLOR: &str = "color";
+
+/// When colored output is used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Decide based on the environment and whether the stream is a terminal
+    #[default]
+    Auto,
+    /// Always color the output
+    Always,
+    /// Never color the output
+    Never,
+}
+
+/// A standard stream output is written to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    /// The standard output stream
+    Stdout,
+    /// The standard error stream
+    Stderr,
+}
+
+impl Stream {
+    /// Whether the stream is connected to a terminal
+    #[must_use]
+    pub fn is_terminal(self) -> bool {
+        match self {
+            Stream::Stdout => std::io::stdout().is_terminal(),
+            Stream::Stderr => std::io::stderr().is_terminal(),
+        }
+    }
+}
+
+impl ColorChoice {
+    /// Resolve the choice using the given environment lookup and terminal check
+    pub(crate) fn resolve(
+        self,
+        var: impl Fn(&str) -> Option<String>,
+        is_terminal: impl FnOnce() -> bool,
+    ) -> bool {
+        let set = |name: &str| var(name).filter(|value| !value.is_empty());
+
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto if set("NO_COLOR").is_some() => false,
+            ColorChoice::Auto if set("CLICOLOR_FORCE").is_some_and(|value| value != "0") => true,
+            ColorChoice::Auto if var("TERM").as_deref() == Some("dumb") => false,
+            ColorChoice::Auto => is_terminal(),
+        }
+    }
+
+    /// Get the built-in global argument overriding the choice
+    #[must_use]
+    pub fn arg() -> Arg {
+        Arg::new(COLOR)
+            .set_long("color")
+            .set_value_name("WHEN")
+            .set_help("When to use colors: auto, always or never")
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err("expected auto, always or never".to_string()),
+        }
+    }
+}
+
+impl Display for ColorChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorChoice::Auto => write!(f, "auto"),
+            ColorChoice::Always => write!(f, "always"),
+            ColorChoice::Never => write!(f, "never"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use super::*;
+
+    fn resolve(choice: ColorChoice, vars: &[(&str, &str)], is_terminal: bool) -> bool {
+        choice.resolve(
+            |name| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| (*value).to_string())
+            },
+            || is_terminal,
+        )
+    }
+
+    #[test]
+    fn color_choice() {
+        assert!(resolve(ColorChoice::Auto, &[], true));
+        assert!(!resolve(ColorChoice::Auto, &[], false));
+        assert!(!resolve(ColorChoice::Auto, &[("NO_COLOR", "1")], true));
+        assert!(resolve(
+            ColorChoice::Auto,
+            &[("CLICOLOR_FORCE", "1")],
+            false
+        ));
+        assert!(!resolve(
+            ColorChoice::Auto,
+            &[("CLICOLOR_FORCE", "0")],
+            false
+        ));
+        assert!(!resolve(ColorChoice::Auto, &[("TERM", "dumb")], true));
+        assert!(resolve(ColorChoice::Always, &[("NO_COLOR", "1")], false));
+        assert!(!resolve(ColorChoice::Never, &[], true));
+        assert_eq!("never".parse(), Ok(ColorChoice::Never));
+        assert!("sometimes".parse::<ColorChoice>().is_err());
+    }
+}
@@ -2,6 +2,8 @@
 
 use dyn_clone::DynClone;
 
+use crate::{arg::Arg, context::Context, error::Result, theme::Theme, usage};
+
 /// The trait that all structs formed from the `command` attribute macro implement.
 ///
 /// This trait provides an interface for `App` to the command created.
@@ -11,6 +13,9 @@ use dyn_clone::DynClone;
 /// `name` - The name of the command.
 /// `description` - The description of the command showed in the help message.
 /// `disabled` - The attribute that specifies if a certain command is enabled or not. Defaults to false.
+/// `args` - The spec of the arguments accepted by the command. Defaults to no arguments.
+/// `category` - The category the command is grouped under in the help message. Defaults to none.
+/// `examples` - Example invocations shown in the help message of the command. Defaults to none.
 ///
 /// The `call` associate function invokes the callback of the command and passes the Context formed by `App` to it.
 pub trait Command: DynClone {
@@ -20,12 +25,81 @@ pub trait Command: DynClone {
     fn description(&self) -> String;
     /// Get the enabled status of the command
     fn disabled(&self) -> bool;
+    /// Get the spec of the arguments accepted by the command
+    fn args(&self) -> Vec<Arg> {
+        vec![]
+    }
+    /// Get the category of the command
+    fn category(&self) -> Option<String> {
+        None
+    }
+    /// Get the example invocations of the command
+    fn examples(&self) -> Vec<Example> {
+        vec![]
+    }
     /// Invoke the command
-    fn call(&self);
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the arguments cannot be converted or the callback fails.
+    fn call(&self, ctx: &Context) -> Result;
 }
 
 dyn_clone::clone_trait_object!(Command);
 
+/// Render the value returned by a callback declared with `#[command(output)]`
+#[doc(hidden)]
+#[cfg(feature = "formats")]
+#[macro_export]
+macro_rules! __render_output {
+    ($ctx:expr, $result:expr) => {
+        $crate::format::render_result($ctx, $result)
+    };
+}
+
+/// Reject callbacks declared with `#[command(output)]`, whose values cannot be rendered without the `formats` feature
+#[doc(hidden)]
+#[cfg(not(feature = "formats"))]
+#[macro_export]
+macro_rules! __render_output {
+    ($ctx:expr, $result:expr) => {
+        compile_error!("#[command(output)] requires the `formats` feature of dock")
+    };
+}
+
+/// An example invocation of a command shown in its help message
+///
+/// ```rs,no_run
+/// Example::new("tool deploy --env prod").set_caption("Deploy to production")
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    /// The full invocation, starting with the program name
+    pub command: String,
+    /// Text describing what the invocation does
+    pub caption: Option<String>,
+}
+
+impl Example {
+    /// Create a new example from the full invocation
+    #[must_use]
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            caption: None,
+        }
+    }
+
+    /// Property setter
+    ///
+    /// Sets the text describing what the invocation does
+    #[must_use]
+    pub fn set_caption(mut self, caption: &str) -> Self {
+        self.caption = Some(caption.to_string());
+        self
+    }
+}
+
 impl std::fmt::Debug for dyn Command {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.name())
@@ -38,12 +112,22 @@ impl dyn Command {
         format!("{} {}", self.name(), self.description())
     }
 
-    /// Displays the command in the colored representation
-    pub fn display_colored(&self) -> String {
+    /// Displays the command in the colored representation using the styles of the theme
+    pub fn display_colored(&self, theme: &Theme) -> String {
         format!(
             "{} {}",
-            crate::Color::Green.paint(self.name()),
+            theme.command.paint(self.name()),
             self.description()
         )
     }
+
+    /// Get the usage line of the command, such as `tool build [OPTIONS] <TARGET>`
+    pub fn usage(&self, program: &str) -> String {
+        usage::command_usage(program, self, None)
+    }
+
+    /// Get the usage line of the command colored using the styles of the theme
+    pub fn usage_colored(&self, program: &str, theme: &Theme) -> String {
+        usage::command_usage(program, self, Some(theme))
+    }
 }
@@ -41,6 +41,10 @@ impl AppConfig {
     }
 
     /// Construct the application config based on the crate details
+    ///
+    /// # Panics
+    ///
+    /// Panics if `Cargo.toml` cannot be read or parsed.
     #[must_use]
     pub fn from_crate() -> Self {
         let config = toml::from_str::<CargoConfig>(
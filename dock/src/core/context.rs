@@ -1,7 +1,21 @@
 //! The part of the Dock application that handles context which is passed to commands when invoked
 
-use crate::{command::Command, App};
-use std::{env::consts, io::Result, path::PathBuf};
+#[cfg(feature = "formats")]
+use crate::format::{self, Format, OUTPUT};
+use crate::{
+    arg::{ArgMatches, Args},
+    color::{ColorChoice, Stream},
+    command::Command,
+    error::{Error, Result as DockResult},
+    output::{Writer, QUIET},
+    progress::Progress,
+    prompt::{Prompter, NO_INPUT, YES},
+    table::Table,
+    terminal, App,
+};
+#[cfg(feature = "formats")]
+use serde::Serialize;
+use std::{collections::HashMap, env::consts, ffi::OsString, io::Write, path::PathBuf};
 
 /// Holds contextual information about a command execution
 ///
@@ -15,7 +29,8 @@ use std::{env::consts, io::Result, path::PathBuf};
 ///
 /// ```rs,norun
 ///
-/// println!("This command was invoked on a {} computer", ctx.env.os)
+/// println!("This command was invoked on a {} computer", ctx.env.os);
+/// println!("Running in {:?} as {:?}", ctx.env.current_dir, ctx.env.env_var("USER"));
 ///
 /// ```
 /// where ctx is an instance of `Context` passed into a callback.
@@ -25,47 +40,358 @@ use std::{env::consts, io::Result, path::PathBuf};
 pub struct Context {
     /// The command that was invoked
     pub command: Box<dyn Command>,
+    /// The arguments parsed for the command
+    pub matches: ArgMatches,
     /// Information about the environment of program execution
     pub env: Environment,
     /// Application struct
     pub app: App,
+    /// When colored output is used, after applying the `--color` global argument
+    pub color: ColorChoice,
 }
 
 impl Context {
-    /// Construct a new Context instance based on the command and the application
+    /// Construct a new Context instance based on the command, its parsed arguments and the application
+    ///
+    /// The environment is a snapshot of the process environment unless replaced through [`Context::set_env`].
     #[must_use]
-    pub fn new(command: Box<dyn Command>, app: App) -> Self {
+    pub fn new(command: Box<dyn Command>, matches: ArgMatches, app: App) -> Self {
         Self {
             command,
+            matches,
             env: Environment::default(),
             app,
+            color: ColorChoice::default(),
         }
     }
+
+    /// Property setter
+    ///
+    /// Sets the environment the command is invoked in
+    #[must_use]
+    pub fn set_env(mut self, env: Environment) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Whether output written to the stream should be colored
+    #[must_use]
+    pub fn should_color(&self, stream: Stream) -> bool {
+        self.color.resolve(
+            |name| self.env.env_var(name).map(str::to_string),
+            || self.app.output.is_terminal(stream),
+        )
+    }
+
+    /// Whether quiet mode is enabled by the `quiet` global flag, such as `-q` when logging is enabled
+    #[must_use]
+    pub fn is_quiet(&self) -> bool {
+        self.matches.is_present(QUIET)
+    }
+
+    /// Get a writer for the standard output stream
+    ///
+    /// Colors are stripped when they are disabled, nothing is written in quiet mode and the output is captured
+    /// when the application runs through the test harness.
+    ///
+    /// ```rs,no_run
+    /// writeln!(ctx.out(), "Built {} targets", targets.len())?;
+    /// ```
+    #[must_use]
+    pub fn out(&self) -> Writer {
+        self.app
+            .output
+            .writer(Stream::Stdout)
+            .set_strip_ansi(!self.should_color(Stream::Stdout))
+            .set_discard(self.is_quiet())
+    }
+
+    /// Get a writer for the standard error stream
+    ///
+    /// Colors are stripped when they are disabled and the output is captured when the application runs through the
+    /// test harness. Unlike [`Context::out`], the stream is not silenced in quiet mode.
+    #[must_use]
+    pub fn err(&self) -> Writer {
+        self.app
+            .output
+            .writer(Stream::Stderr)
+            .set_strip_ansi(!self.should_color(Stream::Stderr))
+    }
+
+    /// Print a message labelled `success:` to the standard output stream, unless in quiet mode
+    pub fn success(&self, message: &str) {
+        let _ = writeln!(
+            self.out(),
+            "{} {message}",
+            self.app.theme.success.paint("success:")
+        );
+    }
+
+    /// Print a message labelled `info:` to the standard output stream, unless in quiet mode
+    pub fn info(&self, message: &str) {
+        let _ = writeln!(
+            self.out(),
+            "{} {message}",
+            self.app.theme.info.paint("info:")
+        );
+    }
+
+    /// Print a message labelled `warning:` to the standard error stream
+    pub fn warn(&self, message: &str) {
+        let _ = writeln!(
+            self.err(),
+            "{} {message}",
+            self.app.theme.warning.paint("warning:")
+        );
+    }
+
+    /// Print a message labelled `error:` to the standard error stream
+    pub fn error(&self, message: &str) {
+        let _ = writeln!(
+            self.err(),
+            "{} {message}",
+            self.app.theme.error.paint("error:")
+        );
+    }
+
+    /// Get the output format chosen through the `--output` global argument, which defaults to a table
+    #[cfg(feature = "formats")]
+    #[must_use]
+    pub fn format(&self) -> Format {
+        self.matches.get(OUTPUT).ok().flatten().unwrap_or_default()
+    }
+
+    /// Render the value in the chosen output format to the standard output stream
+    ///
+    /// ```rs,no_run
+    /// #[command]
+    /// fn list(ctx: &Context) -> Result {
+    ///     ctx.render(&pods()?)
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Custom`](crate::error::Error::Custom) if the value cannot be serialized or written.
+    #[cfg(feature = "formats")]
+    pub fn render<T: Serialize + ?Sized>(&self, value: &T) -> DockResult {
+        let rendered = match self.format() {
+            Format::Table => return self.print_table(&format::table(&format::to_value(value)?)),
+            format => format.render(value)?,
+        };
+
+        writeln!(self.out(), "{rendered}").map_err(|err| Error::Custom(err.to_string()))
+    }
+
+    /// Print the table to the standard output stream
+    ///
+    /// Unless set on the table, the width is the width of the terminal and the headers are painted with the
+    /// heading style of the application theme when colors are enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Custom`](crate::error::Error::Custom) if the table cannot be written.
+    pub fn print_table(&self, table: &Table) -> DockResult {
+        if table.is_empty() {
+            return Ok(());
+        }
+
+        let mut table = table.clone();
+        if table.width().is_none() {
+            table = table.set_width(terminal::width(&self.env, &self.app.output));
+        }
+        if self.should_color(Stream::Stdout) {
+            table = table.set_header_style(self.app.theme.heading);
+        }
+
+        writeln!(self.out(), "{}", table.render()).map_err(|err| Error::Custom(err.to_string()))
+    }
+
+    /// Get a prompter asking the user questions on the standard error stream
+    ///
+    /// Questions fail with [`Error::NoInput`] when the standard input is not a terminal or `--no-input` is passed,
+    /// and confirmations are answered with yes when `-y/--yes` is passed.
+    #[must_use]
+    pub fn prompt(&self) -> Prompter {
+        let prompter = Prompter::new(self.app.output.clone(), self.app.answers.clone())
+            .set_assume_yes(self.matches.is_present(YES))
+            .set_theme(self.should_color(Stream::Stderr).then_some(self.app.theme));
+
+        if self.matches.is_present(NO_INPUT) {
+            prompter.set_interactive(false)
+        } else {
+            prompter
+        }
+    }
+
+    /// Get a display drawing progress bars and spinners on the standard error stream
+    ///
+    /// Bars are reported as periodic lines of text when the stream is not a terminal, and nothing is shown in quiet
+    /// mode.
+    #[must_use]
+    pub fn progress(&self) -> Progress {
+        Progress::new(&self.app.output)
+            .set_hidden(self.is_quiet())
+            .set_width(terminal::width(&self.env, &self.app.output))
+            .set_theme(self.should_color(Stream::Stderr).then_some(self.app.theme))
+    }
+
+    /// Construct a group of arguments, such as the global arguments of the application, from the parsed matches
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a required value is missing or a value cannot be converted.
+    pub fn args<T: Args>(&self) -> DockResult<T> {
+        T::from_context(self)
+    }
 }
 
 /// Holds core information about the execution environment.
-#[allow(unused)]
+///
+/// The default instance is a snapshot of the process environment taken when it is constructed.
+/// An explicit environment can be built with [`Environment::new`] to invoke commands deterministically in tests.
+///
+/// ```rs,no_run
+/// let env = Environment::new()
+///     .set_var("TOKEN", "secret")
+///     .set_current_dir("/tmp")
+///     .set_argv(["tool", "deploy"]);
+///
+/// app().run_with(env)
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Environment {
-    /// The operating system on which the app is run
-    os: String,
-    /// Local environment variables
-    env: std::env::Vars,
+    /// The operating system on which the app is run, such as `linux`
+    pub os: String,
+    /// The CPU architecture on which the app is run, such as `x86_64`
+    pub arch: String,
+    /// The host name of the machine, if it can be detected
+    pub hostname: Option<String>,
+    /// Local environment variables, excluding those that are not valid unicode
+    pub vars: HashMap<String, String>,
     /// Argv passed for the execution
-    argv: std::env::Args,
-    /// The current working directory
-    current_dir: Result<PathBuf>,
-    /// The full filesystem path of the current running executable
-    current_exe: Result<PathBuf>,
+    pub argv: Vec<OsString>,
+    /// The current working directory, if it is accessible
+    pub current_dir: Option<PathBuf>,
+    /// The full filesystem path of the current running executable, if it can be determined
+    pub current_exe: Option<PathBuf>,
+    /// How many scripts the execution is nested in, such as a script running another one
+    pub(crate) script_depth: usize,
+}
+
+impl Environment {
+    /// Construct an environment without variables, arguments or directories
+    ///
+    /// The operating system and architecture are those of the current process.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            os: consts::OS.to_string(),
+            arch: consts::ARCH.to_string(),
+            hostname: None,
+            vars: HashMap::new(),
+            argv: vec![],
+            current_dir: None,
+            current_exe: None,
+            script_depth: 0,
+        }
+    }
+
+    /// Property setter
+    ///
+    /// Sets the value of an environment variable
+    #[must_use]
+    pub fn set_var(mut self, name: &str, value: &str) -> Self {
+        self.vars.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets all the environment variables, replacing the existing ones
+    #[must_use]
+    pub fn set_vars(mut self, vars: HashMap<String, String>) -> Self {
+        self.vars = vars;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the arguments passed for the execution, the first of which is the program name
+    #[must_use]
+    pub fn set_argv<I, T>(mut self, argv: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        self.argv = argv.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the current working directory
+    #[must_use]
+    pub fn set_current_dir(mut self, current_dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(current_dir.into());
+        self
+    }
+
+    /// Get the value of an environment variable
+    #[must_use]
+    pub fn env_var(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).map(String::as_str)
+    }
 }
 
 impl Default for Environment {
     fn default() -> Self {
         Self {
             os: consts::OS.to_string(),
-            env: std::env::vars(),
-            argv: std::env::args(),
-            current_dir: std::env::current_dir(),
-            current_exe: std::env::current_exe(),
+            arch: consts::ARCH.to_string(),
+            hostname: gethostname::gethostname().into_string().ok(),
+            vars: std::env::vars_os()
+                .filter_map(|(name, value)| {
+                    Some((name.into_string().ok()?, value.into_string().ok()?))
+                })
+                .collect(),
+            argv: std::env::args_os().collect(),
+            current_dir: std::env::current_dir().ok(),
+            current_exe: std::env::current_exe().ok(),
+            script_depth: 0,
         }
     }
 }
+
+#[cfg(test)]
+mod app_tests {
+
+    use super::*;
+
+    #[test]
+    fn environment_snapshot() {
+        std::env::set_var("DOCK_CONTEXT_TEST", "1");
+        let env = Environment::default();
+
+        assert_eq!(env.env_var("DOCK_CONTEXT_TEST"), Some("1"));
+        assert_eq!(env.os, consts::OS);
+        assert_eq!(env.clone(), env);
+        assert_eq!(env.current_dir, std::env::current_dir().ok());
+    }
+
+    #[test]
+    fn explicit_environment() {
+        let env = Environment::new()
+            .set_var("TOKEN", "secret")
+            .set_current_dir("/srv")
+            .set_argv(["tool", "deploy"]);
+
+        assert_eq!(env.env_var("TOKEN"), Some("secret"));
+        assert_eq!(env.env_var("HOME"), None);
+        assert_eq!(env.current_dir, Some(PathBuf::from("/srv")));
+        assert_eq!(
+            env.argv,
+            vec![OsString::from("tool"), OsString::from("deploy")]
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Part of the Dock application that handles errors raised while parsing and invoking commands
+
+use std::fmt::{self, Display};
+
+/// Represents an error raised by Dock or by a command callback
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The invoked command is not registered to the application
+    UnknownCommand(String),
+    /// An argument was passed that is not part of the argument spec
+    UnknownArgument(String),
+    /// More positional values were passed than the argument spec accepts
+    UnexpectedValue(String),
+    /// A required argument was not passed
+    MissingArgument(String),
+    /// An argument was passed along with another one it cannot be used with
+    ConflictingArgument {
+        /// The argument that was passed
+        arg: String,
+        /// The argument or command it cannot be used with
+        with: String,
+    },
+    /// An argument that takes a value was passed without one
+    MissingValue(String),
+    /// An argument passed to the process is not valid Unicode
+    InvalidUnicode(String),
+    /// A value could not be converted into the type of its argument
+    InvalidValue {
+        /// The argument the value was passed to
+        arg: String,
+        /// The raw value that was passed
+        value: String,
+        /// The reason the conversion failed
+        reason: String,
+    },
+    /// A prompt could not be answered because input is not interactive or was disabled with `--no-input`
+    NoInput(String),
+    /// A prompt was interrupted with `Ctrl-C` or cancelled with `Escape`
+    Interrupted,
+    /// An external command exited with a non-zero exit code
+    External {
+        /// The name of the command, without the program name
+        command: String,
+        /// The exit code of the executable
+        code: i32,
+    },
+    /// A command of a script failed
+    Script {
+        /// The numbers of the lines that failed, starting from one
+        lines: Vec<usize>,
+        /// The error of the first line that failed
+        error: Box<Error>,
+    },
+    /// A command callback panicked
+    Panic {
+        /// The name of the command
+        command: String,
+        /// The message the callback panicked with
+        message: String,
+    },
+    /// An error raised by a command callback
+    Custom(String),
+}
+
+/// Result type used throughout Dock
+pub type Result<T = ()> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Get the exit code the process should terminate with when this error is returned
+    #[must_use]
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::External { code, .. } => *code,
+            Error::Script { error, .. } => error.exit_code(),
+            Error::Panic { .. } => 101,
+            Error::Interrupted => 130,
+            err if err.is_usage() => 2,
+            _ => 1,
+        }
+    }
+
+    /// Whether the error was caused by invalid usage, in which case the usage line is shown along with it
+    #[must_use]
+    pub fn is_usage(&self) -> bool {
+        !matches!(
+            self,
+            Error::NoInput(_)
+                | Error::Interrupted
+                | Error::External { .. }
+                | Error::Script { .. }
+                | Error::Panic { .. }
+                | Error::Custom(_)
+        )
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownCommand(name) => write!(f, "unknown command '{name}'"),
+            Error::UnknownArgument(arg) => write!(f, "unknown argument '{arg}'"),
+            Error::UnexpectedValue(value) => write!(f, "unexpected value '{value}'"),
+            Error::MissingArgument(arg) => write!(f, "missing required argument '{arg}'"),
+            Error::ConflictingArgument { arg, with } => {
+                write!(f, "argument '{arg}' cannot be used with '{with}'")
+            }
+            Error::MissingValue(arg) => write!(f, "argument '{arg}' expects a value"),
+            Error::InvalidUnicode(arg) => write!(f, "argument '{arg}' is not valid unicode"),
+            Error::InvalidValue { arg, value, reason } => {
+                write!(f, "invalid value '{value}' for '{arg}': {reason}")
+            }
+            Error::NoInput(question) => {
+                write!(
+                    f,
+                    "cannot ask '{question}' because input is not interactive"
+                )
+            }
+            Error::Interrupted => write!(f, "interrupted"),
+            Error::External { command, code } => {
+                write!(f, "external command '{command}' exited with code {code}")
+            }
+            Error::Script { lines, .. } => match lines.as_slice() {
+                [line] => write!(f, "script failed at line {line}"),
+                lines => write!(
+                    f,
+                    "script failed at lines {}",
+                    lines
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
+            },
+            Error::Panic { command, message } => {
+                write!(f, "command '{command}' panicked: {message}")
+            }
+            Error::Custom(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Converts the return value of a command callback into a Dock [`Result`]
+///
+/// This trait is used by the code generated from the `command` attribute macro, which allows callbacks to return
+/// either nothing or a `Result` whose error can be displayed.
+pub trait IntoResult {
+    /// Perform the conversion
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Custom`] holding the displayed error if the callback failed.
+    fn into_result(self) -> Result;
+}
+
+impl IntoResult for () {
+    fn into_result(self) -> Result {
+        Ok(())
+    }
+}
+
+impl<E: Display> IntoResult for std::result::Result<(), E> {
+    fn into_result(self) -> Result {
+        self.map_err(|err| Error::Custom(err.to_string()))
+    }
+}
@@ -0,0 +1,270 @@
+//! Part of the Dock application that runs external commands found on `PATH`
+//!
+//! Once [`App::enable_external_commands`](crate::App::enable_external_commands) is called, an unknown command such as
+//! `tool deploy` runs the `tool-deploy` executable found in the directories listed in `PATH`, like `git foo` runs
+//! `git-foo`. The arguments following the command name are forwarded as they were passed, the executable inherits
+//! the environment of the application and its exit code becomes the one of the application.
+//!
+//! External commands are listed in the help message of the application with an `(external)` marker, and
+//! `tool help deploy` runs `tool-deploy --help`.
+
+use std::{
+    env,
+    ffi::OsString,
+    io::Write,
+    path::{Path, PathBuf},
+    process,
+};
+
+use crate::{
+    command::Command,
+    context::{Context, Environment},
+    error::{Error, Result},
+};
+
+/// Description of external commands in help messages
+pub const MARKER: &str = "(external)";
+
+/// A command run by an executable named after the program, such as `tool-deploy` for `tool deploy`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalCommand {
+    /// Name of the command, without the program name
+    pub name: String,
+    /// Path of the executable
+    pub path: PathBuf,
+    /// Arguments forwarded to the executable
+    pub args: Vec<OsString>,
+}
+
+impl ExternalCommand {
+    /// Create the command run by the executable at the path
+    #[must_use]
+    pub fn new(name: &str, path: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.to_string(),
+            path: path.into(),
+            args: vec![],
+        }
+    }
+
+    /// Property setter
+    ///
+    /// Sets the arguments forwarded to the executable
+    #[must_use]
+    pub fn set_args<I, T>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+impl ExternalCommand {
+    /// Build the process running the executable in the environment
+    ///
+    /// The environment only holds variables that are valid Unicode, so rather than clearing the inherited variables,
+    /// only those it could hold but does not are removed. Other variables are inherited as they are.
+    fn process(&self, env: &Environment) -> process::Command {
+        let mut command = process::Command::new(&self.path);
+        command.args(&self.args);
+
+        for (name, value) in std::env::vars_os() {
+            if let (Some(name), Some(_)) = (name.to_str(), value.to_str()) {
+                if !env.vars.contains_key(name) {
+                    command.env_remove(name);
+                }
+            }
+        }
+        command.envs(&env.vars);
+
+        if let Some(current_dir) = &env.current_dir {
+            command.current_dir(current_dir);
+        }
+
+        command
+    }
+}
+
+impl Command for ExternalCommand {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn description(&self) -> String {
+        MARKER.to_string()
+    }
+
+    fn disabled(&self) -> bool {
+        false
+    }
+
+    fn call(&self, ctx: &Context) -> Result {
+        let mut command = self.process(&ctx.env);
+
+        let failed = |err: std::io::Error| {
+            Error::Custom(format!("failed to run '{}': {err}", self.path.display()))
+        };
+
+        // Captured output cannot be inherited, so it is collected and written once the executable exits
+        let status = if ctx.app.output.is_captured() {
+            let output = command.output().map_err(failed)?;
+            let _ = ctx.out().write_all(&output.stdout);
+            let _ = ctx.err().write_all(&output.stderr);
+            output.status
+        } else {
+            command.status().map_err(failed)?
+        };
+
+        match status.code() {
+            Some(0) => Ok(()),
+            code => Err(Error::External {
+                command: self.name.clone(),
+                code: code.unwrap_or(1),
+            }),
+        }
+    }
+}
+
+/// Find the external commands of the program in the directories listed in `path`, sorted by name
+///
+/// When several directories hold an executable with the same name, the first one wins like it does in a shell.
+#[must_use]
+pub fn discover(program: &str, path: Option<&str>) -> Vec<ExternalCommand> {
+    let prefix = format!("{program}-");
+    let mut commands: Vec<ExternalCommand> = vec![];
+    if program.is_empty() {
+        return commands;
+    }
+
+    for dir in search_path(path) {
+        let Ok(entries) = dir.read_dir() else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let Some(name) = file_name
+                .to_str()
+                .and_then(|file_name| file_name.strip_prefix(&prefix))
+                .and_then(|name| name.strip_suffix(env::consts::EXE_SUFFIX))
+            else {
+                continue;
+            };
+
+            if !name.is_empty()
+                && is_executable(&entry.path())
+                && !commands.iter().any(|command| command.name == name)
+            {
+                commands.push(ExternalCommand::new(name, entry.path()));
+            }
+        }
+    }
+
+    commands.sort_by(|a, b| a.name.cmp(&b.name));
+    commands
+}
+
+/// Find the external command of the program with the name in the directories listed in `path`
+#[must_use]
+pub fn find(program: &str, name: &str, path: Option<&str>) -> Option<ExternalCommand> {
+    if program.is_empty() || name.is_empty() || name.contains(std::path::is_separator) {
+        return None;
+    }
+
+    search_path(path)
+        .map(|dir| dir.join(format!("{program}-{name}{}", env::consts::EXE_SUFFIX)))
+        .find(|path| is_executable(path))
+        .map(|path| ExternalCommand::new(name, path))
+}
+
+/// Directories listed in `path`, leaving out the empty entries that would otherwise stand for the current directory
+fn search_path(path: Option<&str>) -> impl Iterator<Item = PathBuf> + '_ {
+    env::split_paths(path.unwrap_or_default()).filter(|dir| !dir.as_os_str().is_empty())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use std::ffi::OsStr;
+
+    use super::*;
+
+    #[test]
+    fn process_environment() {
+        let env = Environment::new().set_var("DEPLOY_TOKEN", "secret");
+        let command = ExternalCommand::new("deploy", "tool-deploy").process(&env);
+        let envs = command.get_envs().collect::<Vec<_>>();
+
+        assert!(envs.contains(&(OsStr::new("DEPLOY_TOKEN"), Some(OsStr::new("secret")))));
+        for (name, value) in std::env::vars_os() {
+            if name != "DEPLOY_TOKEN" && value.to_str().is_some() && name.to_str().is_some() {
+                assert!(envs.contains(&(name.as_os_str(), None)));
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn discovery() {
+        use std::{fs, os::unix::fs::PermissionsExt};
+
+        let root = env::temp_dir().join(format!("dock-external-{}", process::id()));
+        let (first, second) = (root.join("first"), root.join("second"));
+        for dir in [&first, &second] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        for (path, mode) in [
+            (first.join("tool-deploy"), 0o755),
+            (first.join("tool-notes"), 0o644),
+            (second.join("tool-deploy"), 0o755),
+            (second.join("tool-build"), 0o755),
+            (second.join("other-build"), 0o755),
+        ] {
+            fs::write(&path, "#!/bin/sh\n").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        }
+        let path = env::join_paths([&first, &second]).unwrap();
+        let path = path.to_str();
+
+        assert_eq!(
+            discover("tool", path),
+            vec![
+                ExternalCommand::new("build", second.join("tool-build")),
+                ExternalCommand::new("deploy", first.join("tool-deploy")),
+            ]
+        );
+        assert_eq!(
+            find("tool", "deploy", path),
+            Some(ExternalCommand::new("deploy", first.join("tool-deploy")))
+        );
+        assert_eq!(find("tool", "notes", path), None);
+        assert_eq!(find("tool", "../second/tool-build", path), None);
+        assert!(discover("", path).is_empty());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn empty_path_entries() {
+        let dirs = search_path(Some(":/usr/bin::/bin:")).collect::<Vec<PathBuf>>();
+
+        assert_eq!(dirs, [PathBuf::from("/usr/bin"), PathBuf::from("/bin")]);
+        assert_eq!(search_path(Some("")).count(), 0);
+        assert_eq!(search_path(None).count(), 0);
+    }
+}
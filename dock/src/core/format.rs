@@ -0,0 +1,232 @@
+//! Part of the Dock application that renders values returned by commands in the format chosen by the user
+//!
+//! This module is enabled by the `formats` feature. [`App::enable_output_formats`](crate::App::enable_output_formats)
+//! registers the `--output <FORMAT>` global argument, also accepted as `--format`. Commands render serializable values
+//! through [`Context::render`](crate::context::Context::render), or by returning them from a callback declared with
+//! `#[command(output)]`, as a table for humans or as JSON, NDJSON or YAML for scripts.
+
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    arg::Arg,
+    context::Context,
+    error::{Error, Result},
+    table::Table,
+};
+
+/// Identifier of the built-in global output format argument
+pub const OUTPUT: &str = "output";
+
+/// The format values returned by commands are rendered in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Aligned columns meant to be read by humans
+    #[default]
+    Table,
+    /// Pretty printed JSON
+    Json,
+    /// One compact JSON document per line, with one line per item of a list
+    Ndjson,
+    /// YAML
+    Yaml,
+}
+
+impl Format {
+    /// Get the built-in global argument choosing the format
+    #[must_use]
+    pub fn arg() -> Arg {
+        Arg::new(OUTPUT)
+            .set_long("output")
+            .set_alias("format")
+            .set_value_name("FORMAT")
+            .set_help("Output format: table, json, ndjson or yaml")
+    }
+
+    /// Render the value in the format
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Custom`] if the value cannot be serialized.
+    pub fn render<T: Serialize + ?Sized>(self, value: &T) -> Result<String> {
+        match self {
+            Format::Table => Ok(table(&to_value(value)?).render()),
+            Format::Json => serde_json::to_string_pretty(value).map_err(custom),
+            Format::Ndjson => match to_value(value)? {
+                Value::Array(items) => items
+                    .iter()
+                    .map(serde_json::to_string)
+                    .collect::<serde_json::Result<Vec<String>>>()
+                    .map(|lines| lines.join("\n"))
+                    .map_err(custom),
+                value => serde_json::to_string(&value).map_err(custom),
+            },
+            Format::Yaml => serde_norway::to_string(value)
+                .map(|yaml| yaml.trim_end().to_string())
+                .map_err(custom),
+        }
+    }
+}
+
+/// Render the value returned by a command callback declared with `#[command(output)]`
+///
+/// # Errors
+///
+/// Returns an [`Error::Custom`] holding the displayed error if the callback failed or the value cannot be rendered.
+pub fn render_result<T, E>(ctx: &Context, result: std::result::Result<T, E>) -> Result
+where
+    T: Serialize,
+    E: Display,
+{
+    ctx.render(&result.map_err(custom)?)
+}
+
+fn custom(err: impl Display) -> Error {
+    Error::Custom(err.to_string())
+}
+
+pub(crate) fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value> {
+    serde_json::to_value(value).map_err(custom)
+}
+
+/// Build a table from a list of objects, with a column per key, from an object, with a row per key, or from
+/// anything else, with a row per value
+pub(crate) fn table(value: &Value) -> Table {
+    match value {
+        Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object) => {
+            let mut headers: Vec<&String> = vec![];
+            for key in items
+                .iter()
+                .filter_map(Value::as_object)
+                .flat_map(|item| item.keys())
+            {
+                if !headers.contains(&key) {
+                    headers.push(key);
+                }
+            }
+
+            Table::new()
+                .set_headers(headers.iter().map(|header| header.to_uppercase()).collect())
+                .set_rows(
+                    items
+                        .iter()
+                        .map(|item| {
+                            headers
+                                .iter()
+                                .map(|header| {
+                                    item.get(header.as_str()).map(cell).unwrap_or_default()
+                                })
+                                .collect()
+                        })
+                        .collect(),
+                )
+        }
+        Value::Array(items) => {
+            Table::new().set_rows(items.iter().map(|item| vec![cell(item)]).collect())
+        }
+        Value::Object(map) => Table::new().set_rows(
+            map.iter()
+                .map(|(key, value)| vec![key.clone(), cell(value)])
+                .collect(),
+        ),
+        value => Table::new().add_row(vec![cell(value)]),
+    }
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "ndjson" => Ok(Format::Ndjson),
+            "yaml" => Ok(Format::Yaml),
+            _ => Err("expected table, json, ndjson or yaml".to_string()),
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Table => write!(f, "table"),
+            Format::Json => write!(f, "json"),
+            Format::Ndjson => write!(f, "ndjson"),
+            Format::Yaml => write!(f, "yaml"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Pod {
+        name: String,
+        ready: bool,
+        restarts: Option<u32>,
+    }
+
+    fn pods() -> Vec<Pod> {
+        vec![
+            Pod {
+                name: "web".to_string(),
+                ready: true,
+                restarts: Some(3),
+            },
+            Pod {
+                name: "worker-long".to_string(),
+                ready: false,
+                restarts: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn render_formats() {
+        assert_eq!(
+            Format::Table.render(&pods()).unwrap(),
+            "NAME         READY  RESTARTS\nweb          true   3\nworker-long  false"
+        );
+        assert_eq!(
+            Format::Ndjson.render(&pods()).unwrap(),
+            [
+                r#"{"name":"web","ready":true,"restarts":3}"#,
+                r#"{"name":"worker-long","ready":false,"restarts":null}"#,
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            Format::Json.render(&pods()[0]).unwrap(),
+            "{\n  \"name\": \"web\",\n  \"ready\": true,\n  \"restarts\": 3\n}"
+        );
+        assert_eq!(
+            Format::Yaml.render(&pods()[0]).unwrap(),
+            "name: web\nready: true\nrestarts: 3"
+        );
+        assert_eq!(
+            Format::Table.render(&pods()[0]).unwrap(),
+            "name      web\nready     true\nrestarts  3"
+        );
+        assert_eq!(Format::Table.render(&["a", "b"]).unwrap(), "a\nb");
+        assert_eq!("ndjson".parse(), Ok(Format::Ndjson));
+        assert_eq!("yaml".parse(), Ok(Format::Yaml));
+        assert!("xml".parse::<Format>().is_err());
+    }
+}
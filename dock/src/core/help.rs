@@ -1,9 +1,29 @@
 //! Part of the Dock application that handles help command logic
 
+use ansi_term::Style;
 use dyn_clone::DynClone;
 use std::fmt::Debug;
 
-use crate::{command::Command, config::AppConfig};
+use crate::{
+    arg::Arg,
+    command::Command,
+    config::AppConfig,
+    terminal::{self, display_width, wrap},
+    theme::Theme,
+    usage::{self, placeholder, positional, unique},
+};
+
+/// Indentation of listed items
+const INDENT: usize = 2;
+/// Space between the name and description columns
+const GAP: usize = 2;
+/// Names wider than this are not aligned and their description starts on the next line
+const MAX_NAME_WIDTH: usize = 24;
+/// Descriptions are never wrapped narrower than this
+const MIN_DESCRIPTION_WIDTH: usize = 20;
+
+/// Heading of the group holding commands without a category
+pub const DEFAULT_CATEGORY: &str = "Commands";
 
 /// Represents a default help message
 ///
@@ -15,6 +35,11 @@ use crate::{command::Command, config::AppConfig};
 pub struct DefaultHelpStructure {
     config: Box<AppConfig>,
     commands: Vec<Box<dyn Command>>,
+    theme: Theme,
+    width: usize,
+    categories: Vec<String>,
+    program: String,
+    globals: Vec<Arg>,
 }
 
 /// This trait handles the methods required to print the help command.
@@ -23,6 +48,9 @@ pub struct DefaultHelpStructure {
 ///
 /// If colored output is enabled, `get_help_colored` is called, otherwise `get_Help` is called.
 ///
+/// The help message of a single command, shown for `tool <command> --help`, falls back to the application
+/// help message unless `get_command_help` and `get_command_help_colored` are implemented.
+///
 /// ```rs,no_run
 /// pub struct CustomHelpStructure{}
 ///
@@ -36,8 +64,20 @@ pub trait HelpMessage: DynClone {
     fn get_help(&self) -> String;
     /// Get the color formatted version of the help message
     fn get_help_colored(&self) -> String;
+    /// Get the help message of a single command
+    fn get_command_help(&self, command: &dyn Command) -> String {
+        let _ = command;
+        self.get_help()
+    }
+    /// Get the color formatted version of the help message of a single command
+    fn get_command_help_colored(&self, command: &dyn Command) -> String {
+        let _ = command;
+        self.get_help_colored()
+    }
 }
 
+dyn_clone::clone_trait_object!(HelpMessage);
+
 impl Debug for dyn HelpMessage {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.get_help())
@@ -47,35 +87,55 @@ impl Debug for dyn HelpMessage {
 impl HelpMessage for DefaultHelpStructure {
     fn get_help(&self) -> String {
         format!(
-            r#"
+            r"
+{}
+
+{}
+
 {}
 
 {}
 
 
 {}      
-        "#,
+        ",
             self.build_header(),
+            self.build_usage(None),
             self.build_commands(),
+            self.build_options(&self.globals, None),
             self.build_footer()
         )
     }
 
     fn get_help_colored(&self) -> String {
         format!(
-            r#"
+            r"
+{}
+
+{}
+
 {}
 
 {}
 
 
 {}      
-        "#,
+        ",
             self.build_header_colored(),
+            self.build_usage(Some(&self.theme)),
             self.build_commands_colored(),
+            self.build_options(&self.globals, Some(&self.theme)),
             self.build_footer_colored()
         )
     }
+
+    fn get_command_help(&self, command: &dyn Command) -> String {
+        self.build_command_help(command, None)
+    }
+
+    fn get_command_help_colored(&self, command: &dyn Command) -> String {
+        self.build_command_help(command, Some(&self.theme))
+    }
 }
 
 impl DefaultHelpStructure {
@@ -83,18 +143,296 @@ impl DefaultHelpStructure {
     #[must_use]
     pub fn new(config: AppConfig, commands: Vec<Box<dyn Command>>) -> Self {
         Self {
-            config: Box::new(config),
             commands,
+            theme: Theme::default(),
+            width: terminal::DEFAULT_WIDTH,
+            categories: vec![],
+            program: config.name.clone().unwrap_or_default(),
+            globals: vec![],
+            config: Box::new(config),
+        }
+    }
+
+    /// Property setter
+    ///
+    /// Sets the program name used in usage lines, which defaults to the application name
+    #[must_use]
+    pub fn set_program(mut self, program: &str) -> Self {
+        self.program = program.to_string();
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the global arguments listed as options of the application and of every command
+    #[must_use]
+    pub fn set_global_args(mut self, globals: Vec<Arg>) -> Self {
+        self.globals = globals;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the theme used for the colored help message
+    #[must_use]
+    pub fn set_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the width the help message is laid out for, which defaults to 80 columns
+    #[must_use]
+    pub fn set_width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the order in which command categories are listed
+    ///
+    /// Categories that are not listed follow in the order they first appear, with uncategorized commands first.
+    #[must_use]
+    pub fn set_category_order(mut self, categories: Vec<String>) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    /// Get the width of the name column for the rows
+    fn name_width<'r>(rows: impl Iterator<Item = &'r (String, String)>) -> usize {
+        rows.map(|(name, _)| display_width(name))
+            .filter(|width| *width <= MAX_NAME_WIDTH)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Lay out rows of names and descriptions in two aligned columns
+    ///
+    /// Descriptions are wrapped to the width of the help message with a hanging indent.
+    fn build_columns(
+        &self,
+        rows: &[(String, String)],
+        style: Option<Style>,
+        name_width: usize,
+    ) -> String {
+        let indent = INDENT + name_width + GAP;
+        let description_width = self.width.saturating_sub(indent).max(MIN_DESCRIPTION_WIDTH);
+
+        let mut lines = vec![];
+
+        for (name, description) in rows {
+            let width = display_width(name);
+            let painted = style.map_or_else(|| name.clone(), |style| style.paint(name).to_string());
+            let mut description = wrap(description, description_width).into_iter();
+
+            if width > name_width {
+                lines.push(format!("{:INDENT$}{painted}", ""));
+            } else if let Some(first) = description.next() {
+                let padding = name_width - width + GAP;
+                lines.push(
+                    format!("{:INDENT$}{painted}{:padding$}{first}", "", "")
+                        .trim_end()
+                        .to_string(),
+                );
+            }
+
+            for line in description.filter(|line| !line.is_empty()) {
+                lines.push(format!("{:indent$}{line}", ""));
+            }
         }
+
+        lines.join("\n")
+    }
+
+    /// Group the enabled commands by category in the order they are listed
+    fn command_groups(&self) -> Vec<(String, Vec<(String, String)>)> {
+        let mut groups: Vec<(String, Vec<(String, String)>)> = vec![];
+
+        for command in self.commands.iter().filter(|command| !command.disabled()) {
+            let category = command
+                .category()
+                .unwrap_or_else(|| DEFAULT_CATEGORY.to_string());
+            let row = (command.name(), command.description());
+
+            match groups.iter_mut().find(|(name, _)| *name == category) {
+                Some((_, rows)) => rows.push(row),
+                None if category == DEFAULT_CATEGORY => groups.insert(0, (category, vec![row])),
+                None => groups.push((category, vec![row])),
+            }
+        }
+
+        groups.sort_by_key(|(name, _)| {
+            self.categories
+                .iter()
+                .position(|category| category == name)
+                .unwrap_or(usize::MAX)
+        });
+
+        groups
+    }
+
+    fn build_command_groups(&self, heading: Option<Style>, name: Option<Style>) -> String {
+        let groups = self.command_groups();
+        let name_width = Self::name_width(groups.iter().flat_map(|(_, rows)| rows));
+
+        groups
+            .iter()
+            .map(|(category, rows)| {
+                let category = format!("{category}:");
+                let category = heading.map_or_else(
+                    || category.clone(),
+                    |style| style.paint(&category).to_string(),
+                );
+
+                format!("{category}\n{}", self.build_columns(rows, name, name_width))
+            })
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
+
+    fn paint_heading(heading: &str, theme: Option<&Theme>) -> String {
+        match theme {
+            Some(theme) => theme.heading.paint(heading).to_string(),
+            None => heading.to_string(),
+        }
+    }
+
+    fn build_usage(&self, theme: Option<&Theme>) -> String {
+        format!(
+            "{} {}",
+            Self::paint_heading("Usage:", theme),
+            usage::app_usage(&self.program, &self.globals, theme)
+        )
+    }
+
+    /// List the positional arguments in the spec
+    fn build_arguments(&self, args: &[Arg], theme: Option<&Theme>) -> String {
+        let rows = args
+            .iter()
+            .filter(|arg| arg.is_positional())
+            .map(|arg| (positional(arg), Self::describe(arg)))
+            .collect::<Vec<(String, String)>>();
+
+        self.build_section(
+            "Arguments:",
+            &rows,
+            theme,
+            theme.map(|theme| theme.placeholder),
+        )
+    }
+
+    /// List the named arguments in the spec
+    fn build_options(&self, args: &[Arg], theme: Option<&Theme>) -> String {
+        let rows = args
+            .iter()
+            .filter(|arg| !arg.is_positional())
+            .map(|arg| {
+                let mut name = match (arg.short, &arg.long) {
+                    (Some(short), Some(long)) => format!("-{short}, --{long}"),
+                    (Some(short), None) => format!("-{short}"),
+                    (None, Some(long)) => format!("    --{long}"),
+                    (None, None) => unreachable!(),
+                };
+                if arg.takes_value {
+                    name = format!("{name} {}", placeholder(arg));
+                }
+                (name, Self::describe(arg))
+            })
+            .collect::<Vec<(String, String)>>();
+
+        self.build_section("Options:", &rows, theme, theme.map(|theme| theme.flag))
+    }
+
+    /// Get the help text of an argument along with its default value and environment variable
+    fn describe(arg: &Arg) -> String {
+        let mut description = vec![arg.help.clone()];
+
+        if let Some(default) = &arg.default {
+            description.push(format!("[default: {default}]"));
+        }
+        if let Some(env) = &arg.env {
+            description.push(format!("[env: {env}]"));
+        }
+
+        description.join(" ").trim().to_string()
+    }
+
+    fn build_section(
+        &self,
+        heading: &str,
+        rows: &[(String, String)],
+        theme: Option<&Theme>,
+        style: Option<Style>,
+    ) -> String {
+        if rows.is_empty() {
+            return String::new();
+        }
+
+        format!(
+            "{}\n{}",
+            Self::paint_heading(heading, theme),
+            self.build_columns(rows, style, Self::name_width(rows.iter()))
+        )
+    }
+
+    fn build_command_help(&self, command: &dyn Command, theme: Option<&Theme>) -> String {
+        let args = unique([command.args(), self.globals.clone()].concat());
+
+        let sections = [
+            command.description(),
+            format!(
+                "{} {}",
+                Self::paint_heading("Usage:", theme),
+                usage::command_usage(&self.program, command, theme)
+            ),
+            self.build_arguments(&args, theme),
+            self.build_options(&args, theme),
+            Self::build_examples(command, theme),
+        ];
+
+        sections
+            .into_iter()
+            .filter(|section| !section.is_empty())
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
+
+    fn build_examples(command: &dyn Command, theme: Option<&Theme>) -> String {
+        let examples = command.examples();
+        if examples.is_empty() {
+            return String::new();
+        }
+
+        let indent = " ".repeat(INDENT);
+        let mut lines = vec![Self::paint_heading("Examples:", theme)];
+
+        for example in examples {
+            let invocation = match theme {
+                Some(theme) => theme.command.paint(&example.command).to_string(),
+                None => example.command,
+            };
+
+            match example.caption {
+                Some(caption) => {
+                    lines.push(format!("{indent}{caption}"));
+                    lines.push(format!("{indent}{indent}$ {invocation}"));
+                }
+                None => lines.push(format!("{indent}$ {invocation}")),
+            }
+        }
+
+        lines.join("\n")
     }
 
     fn build_header(&self) -> String {
         let config = self.config.clone();
         format!(
-            r#"
+            r"
 {} {}
 {}
-        "#,
+        ",
             config.name.unwrap_or_default(),
             config.version.unwrap_or_default(),
             config.description.unwrap_or_default(),
@@ -110,26 +448,18 @@ impl DefaultHelpStructure {
 
         format!(
             "{} {}\n{}",
-            crate::Color::Blue.paint(name),
-            crate::Color::Blue.paint(version),
+            self.theme.heading.paint(name),
+            self.theme.heading.paint(version),
             description,
         )
     }
 
     fn build_commands(&self) -> String {
-        self.commands
-            .iter()
-            .map(|i| i.display())
-            .collect::<Vec<String>>()
-            .join("\n")
+        self.build_command_groups(None, None)
     }
 
     fn build_commands_colored(&self) -> String {
-        self.commands
-            .iter()
-            .map(|i| i.display_colored())
-            .collect::<Vec<String>>()
-            .join("\n")
+        self.build_command_groups(Some(self.theme.heading), Some(self.theme.command))
     }
 
     fn build_footer(&self) -> String {
@@ -139,7 +469,179 @@ impl DefaultHelpStructure {
     fn build_footer_colored(&self) -> String {
         format!(
             "{}",
-            crate::Color::Blue.paint(self.config.authors.as_ref().unwrap_or(&vec![]).join(", "))
+            self.theme
+                .heading
+                .paint(self.config.authors.as_ref().unwrap_or(&vec![]).join(", "))
         )
     }
 }
+
+#[cfg(test)]
+mod app_tests {
+
+    use super::*;
+    use crate::command::Example;
+
+    fn config() -> AppConfig {
+        AppConfig {
+            name: Some("dock".to_string()),
+            version: Some("1.2.3".to_string()),
+            ..AppConfig::default()
+        }
+    }
+
+    #[test]
+    fn themed_help() {
+        let plain = DefaultHelpStructure::new(config(), vec![]).set_theme(Theme::plain());
+        let classic = DefaultHelpStructure::new(config(), vec![]);
+
+        assert!(!plain.get_help_colored().contains('\u{1b}'));
+        assert!(classic
+            .get_help_colored()
+            .contains(&Theme::classic().heading.paint("dock").to_string()));
+    }
+
+    #[derive(Clone)]
+    struct Dummy(&'static str, &'static str, Option<&'static str>);
+
+    impl Command for Dummy {
+        fn name(&self) -> String {
+            self.0.to_string()
+        }
+        fn description(&self) -> String {
+            self.1.to_string()
+        }
+        fn disabled(&self) -> bool {
+            false
+        }
+        fn category(&self) -> Option<String> {
+            self.2.map(ToString::to_string)
+        }
+        fn call(&self, _ctx: &crate::context::Context) -> crate::error::Result {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn aligned_columns() {
+        let help = DefaultHelpStructure::new(
+            config(),
+            vec![
+                Box::new(Dummy("build", "Build the project", None)),
+                Box::new(Dummy(
+                    "publish",
+                    "Publish the package to the registry",
+                    None,
+                )),
+                Box::new(Dummy("a-very-long-command-name-indeed", "Too long", None)),
+            ],
+        )
+        .set_width(40);
+
+        assert_eq!(
+            help.build_commands(),
+            [
+                "Commands:",
+                "  build    Build the project",
+                "  publish  Publish the package to the",
+                "           registry",
+                "  a-very-long-command-name-indeed",
+                "           Too long",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn command_categories() {
+        let help = DefaultHelpStructure::new(
+            config(),
+            vec![
+                Box::new(Dummy("ping", "Ping a host", Some("Networking"))),
+                Box::new(Dummy("mount", "Mount a volume", Some("Storage"))),
+                Box::new(Dummy("version", "Print the version", None)),
+                Box::new(Dummy("fetch", "Fetch a file", Some("Networking"))),
+            ],
+        )
+        .set_width(80)
+        .set_category_order(vec!["Storage".to_string()]);
+
+        assert_eq!(
+            help.build_commands(),
+            [
+                "Storage:",
+                "  mount    Mount a volume",
+                "",
+                "Commands:",
+                "  version  Print the version",
+                "",
+                "Networking:",
+                "  ping     Ping a host",
+                "  fetch    Fetch a file",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn command_help() {
+        #[derive(Clone)]
+        struct Build;
+
+        impl Command for Build {
+            fn name(&self) -> String {
+                "build".to_string()
+            }
+            fn description(&self) -> String {
+                "Build the project".to_string()
+            }
+            fn disabled(&self) -> bool {
+                false
+            }
+            fn args(&self) -> Vec<Arg> {
+                vec![
+                    Arg::new("target").set_help("The target to build"),
+                    Arg::new("jobs")
+                        .set_short('j')
+                        .set_long("jobs")
+                        .set_default("4")
+                        .set_help("Number of jobs"),
+                ]
+            }
+            fn examples(&self) -> Vec<Example> {
+                vec![
+                    Example::new("tool build wasm").set_caption("Build for the web"),
+                    Example::new("tool build -j 8"),
+                ]
+            }
+            fn call(&self, _ctx: &crate::context::Context) -> crate::error::Result {
+                Ok(())
+            }
+        }
+
+        let help = DefaultHelpStructure::new(config(), vec![])
+            .set_width(80)
+            .set_program("tool");
+
+        assert_eq!(
+            help.get_command_help(&Build),
+            [
+                "Build the project",
+                "",
+                "Usage: tool build [OPTIONS] [TARGET]",
+                "",
+                "Arguments:",
+                "  [TARGET]  The target to build",
+                "",
+                "Options:",
+                "  -j, --jobs <JOBS>  Number of jobs [default: 4]",
+                "",
+                "Examples:",
+                "  Build for the web",
+                "    $ tool build wasm",
+                "  $ tool build -j 8",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Part of the Dock application that integrates the `log` facade
+//!
+//! Enabled with the `logging` feature. [`App::enable_logging`](crate::App::enable_logging) registers the
+//! `-v/--verbose` and `-q/--quiet` global flags, and the logger is installed with a matching level before the
+//! command is invoked.
+//!
+//! A logger can only be installed once per process, so every later invocation, such as a line of a script, only
+//! updates the level and the destination of the records.
+
+use std::{cell::RefCell, io::Write};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::{
+    arg::{Arg, ArgMatches},
+    color::Stream,
+    output::Output,
+    theme::Theme,
+};
+
+thread_local! {
+    /// Destination of the records logged by the application running on the thread
+    static TARGET: RefCell<Option<Target>> = const { RefCell::new(None) };
+}
+
+/// Where records are written and how they are colored
+struct Target {
+    output: Output,
+    theme: Option<Theme>,
+}
+
+/// Identifier of the global verbosity flag
+pub const VERBOSE: &str = "verbose";
+/// Identifier of the global quiet flag
+pub const QUIET: &str = crate::output::QUIET;
+
+/// Get the global flags controlling the log level
+#[must_use]
+pub fn args() -> Vec<Arg> {
+    vec![
+        Arg::new(VERBOSE)
+            .set_short('v')
+            .set_long("verbose")
+            .set_help("Increase the logging verbosity")
+            .set_takes_value(false),
+        Arg::new(QUIET)
+            .set_short('q')
+            .set_long("quiet")
+            .set_help("Decrease the logging verbosity")
+            .set_takes_value(false),
+    ]
+}
+
+/// Get the level matching the number of verbose and quiet flags passed
+///
+/// Warnings and errors are logged by default, each `-v` enables one more level and each `-q` one less.
+#[must_use]
+pub fn level(verbose: usize, quiet: usize) -> LevelFilter {
+    const LEVELS: [LevelFilter; 6] = [
+        LevelFilter::Off,
+        LevelFilter::Error,
+        LevelFilter::Warn,
+        LevelFilter::Info,
+        LevelFilter::Debug,
+        LevelFilter::Trace,
+    ];
+
+    LEVELS[(2 + verbose).saturating_sub(quiet).min(LEVELS.len() - 1)]
+}
+
+/// Logger writing records to the standard error stream of the application
+///
+/// Records are written to the [`Output`] of the application that installed the logger last on the current thread,
+/// and to the standard error stream of the process on other threads.
+pub struct DockLogger;
+
+impl DockLogger {
+    /// Install the logger with the level matching the parsed global flags, writing records to the output
+    ///
+    /// Records are colored using the theme if one is given. If a logger is already installed, only the maximum level
+    /// and the destination of the records are updated.
+    pub fn init(matches: &ArgMatches, theme: Option<Theme>, output: &Output) {
+        let level = level(matches.occurrences(VERBOSE), matches.occurrences(QUIET));
+        TARGET.with(|target| {
+            *target.borrow_mut() = Some(Target {
+                output: output.clone(),
+                theme,
+            });
+        });
+
+        let _ = log::set_logger(&DockLogger);
+        log::set_max_level(level);
+    }
+
+    fn format(record: &Record<'_>, theme: Option<&Theme>) -> String {
+        let label = record.level().to_string().to_lowercase();
+
+        let Some(theme) = theme else {
+            return format!("{label}: {}", record.args());
+        };
+
+        let style = match record.level() {
+            Level::Error => theme.error,
+            Level::Warn => theme.warning,
+            Level::Info => theme.info,
+            Level::Debug => theme.debug,
+            Level::Trace => theme.trace,
+        };
+
+        format!("{}: {}", style.paint(label), record.args())
+    }
+}
+
+impl Log for DockLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        TARGET.with(|target| match &*target.borrow() {
+            Some(target) => {
+                let line = Self::format(record, target.theme.as_ref());
+                let _ = writeln!(target.output.writer(Stream::Stderr), "{line}");
+            }
+            None => {
+                let _ = writeln!(std::io::stderr(), "{}", Self::format(record, None));
+            }
+        });
+    }
+
+    fn flush(&self) {
+        TARGET.with(|target| match &*target.borrow() {
+            Some(target) => {
+                let _ = target.output.writer(Stream::Stderr).flush();
+            }
+            None => {
+                let _ = std::io::stderr().flush();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use ansi_term::Color;
+
+    use super::*;
+
+    #[test]
+    fn verbosity_level() {
+        assert_eq!(level(0, 0), LevelFilter::Warn);
+        assert_eq!(level(1, 0), LevelFilter::Info);
+        assert_eq!(level(2, 0), LevelFilter::Debug);
+        assert_eq!(level(5, 0), LevelFilter::Trace);
+        assert_eq!(level(0, 1), LevelFilter::Error);
+        assert_eq!(level(0, 3), LevelFilter::Off);
+        assert_eq!(level(1, 1), LevelFilter::Warn);
+    }
+
+    #[test]
+    fn later_level() {
+        let output = Output::captured();
+        let mut matches = ArgMatches::new();
+
+        DockLogger::init(&matches, None, &output);
+        log::warn!("shown");
+        log::debug!("hidden");
+
+        matches.add_occurrence(VERBOSE);
+        matches.add_occurrence(VERBOSE);
+        DockLogger::init(&matches, None, &output);
+        log::debug!("shown once verbose");
+
+        assert_eq!(
+            output.captured_text(Stream::Stderr),
+            "warn: shown\ndebug: shown once verbose\n"
+        );
+    }
+
+    #[test]
+    fn themed_levels() {
+        let theme = Theme {
+            info: Color::Red.normal(),
+            debug: Color::Green.normal(),
+            trace: Color::Blue.normal(),
+            ..Theme::plain()
+        };
+        let label = |level: Level| {
+            DockLogger::format(
+                &Record::builder()
+                    .level(level)
+                    .args(format_args!("message"))
+                    .build(),
+                Some(&theme),
+            )
+        };
+
+        assert_eq!(
+            label(Level::Info),
+            format!("{}: message", Color::Red.paint("info"))
+        );
+        assert_eq!(
+            label(Level::Debug),
+            format!("{}: message", Color::Green.paint("debug"))
+        );
+        assert_eq!(
+            label(Level::Trace),
+            format!("{}: message", Color::Blue.paint("trace"))
+        );
+        assert_eq!(label(Level::Warn), "warn: message");
+    }
+}
@@ -0,0 +1,290 @@
+//! Part of the Dock application that runs cross-cutting behaviour around every command
+//!
+//! A middleware receives the context of the invoked command along with the rest of the chain, which it runs with
+//! [`Next::run`]. It can act before and after the command, skip the command by returning without running the chain,
+//! or transform the result. Middleware registered first wraps the ones registered after it, the command being the
+//! innermost link.
+//!
+//! ```rs,no_run
+//! App::from_crate()
+//!     .register_middleware(|ctx: &Context, next: Next<'_>| {
+//!         let start = Instant::now();
+//!         let result = next.run(ctx);
+//!         log::debug!("{} took {:?}", ctx.command.name(), start.elapsed());
+//!         result
+//!     })
+//!     .register_before_hook(|ctx| check_token(ctx.env.env_var("TOKEN")))
+//!     .run()
+//! ```
+//!
+//! Middleware only runs once a command is found and its arguments are parsed, so it does not run for help messages
+//! or usage errors. A command that panics fails with an [`Error::Panic`], which the middleware sees like any other
+//! error, while the panic message is still printed by the panic hook.
+
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+};
+
+use crate::{
+    command::Command,
+    context::Context,
+    error::{Error, Result},
+};
+
+/// Behaviour run around every command, usually a closure taking the context and the rest of the chain
+pub trait Middleware {
+    /// Run the behaviour, calling [`Next::run`] to invoke the rest of the chain
+    ///
+    /// # Errors
+    ///
+    /// Returns the result of the rest of the chain, an error short-circuiting it or a transformed result.
+    fn handle(&self, ctx: &Context, next: Next<'_>) -> Result;
+}
+
+impl<F> Middleware for F
+where
+    F: Fn(&Context, Next<'_>) -> Result,
+{
+    fn handle(&self, ctx: &Context, next: Next<'_>) -> Result {
+        self(ctx, next)
+    }
+}
+
+/// The rest of the chain following a middleware, ending with the command
+pub struct Next<'a> {
+    middleware: &'a [Arc<dyn Middleware>],
+    command: &'a dyn Command,
+}
+
+impl<'a> Next<'a> {
+    /// Create the chain running the middleware in order around the command
+    pub(crate) fn new(middleware: &'a [Arc<dyn Middleware>], command: &'a dyn Command) -> Self {
+        Self {
+            middleware,
+            command,
+        }
+    }
+
+    /// Run the rest of the chain
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the command or of a middleware in the rest of the chain, or an [`Error::Panic`] if the
+    /// command panics.
+    pub fn run(self, ctx: &Context) -> Result {
+        match self.middleware.split_first() {
+            Some((first, rest)) => first.handle(ctx, Next::new(rest, self.command)),
+            None => panic::catch_unwind(AssertUnwindSafe(|| self.command.call(ctx)))
+                .unwrap_or_else(|payload| {
+                    Err(Error::Panic {
+                        command: self.command.name(),
+                        message: panic_message(&*payload),
+                    })
+                }),
+        }
+    }
+}
+
+/// Get the message a panic was raised with, which is a string unless the panic was raised with another payload
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Middleware running a hook before the command, which is skipped if the hook fails
+pub(crate) fn before(hook: impl Fn(&Context) -> Result) -> impl Middleware {
+    move |ctx: &Context, next: Next<'_>| {
+        hook(ctx)?;
+        next.run(ctx)
+    }
+}
+
+/// Middleware running a hook after the command succeeds
+pub(crate) fn after(hook: impl Fn(&Context) -> Result) -> impl Middleware {
+    move |ctx: &Context, next: Next<'_>| {
+        next.run(ctx)?;
+        hook(ctx)
+    }
+}
+
+/// Middleware running a hook when the rest of the chain fails, whose result replaces the one of the chain
+pub(crate) fn on_error(hook: impl Fn(&Context, Error) -> Result) -> impl Middleware {
+    move |ctx: &Context, next: Next<'_>| next.run(ctx).or_else(|err| hook(ctx, err))
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{arg::ArgMatches, App};
+
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    #[derive(Clone)]
+    struct Deploy(Log, bool);
+
+    impl Command for Deploy {
+        fn name(&self) -> String {
+            "deploy".to_string()
+        }
+        fn description(&self) -> String {
+            "Deploy the application".to_string()
+        }
+        fn disabled(&self) -> bool {
+            false
+        }
+        fn call(&self, _ctx: &Context) -> Result {
+            self.0.lock().unwrap().push("deploy".to_string());
+            if self.1 {
+                Ok(())
+            } else {
+                Err(Error::Custom("deploy failed".to_string()))
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    struct Crash;
+
+    impl Command for Crash {
+        fn name(&self) -> String {
+            "crash".to_string()
+        }
+        fn description(&self) -> String {
+            "Panic while running".to_string()
+        }
+        fn disabled(&self) -> bool {
+            false
+        }
+        fn call(&self, _ctx: &Context) -> Result {
+            panic!("disk {} is on fire", 3);
+        }
+    }
+
+    fn context(command: &(impl Command + Clone + 'static)) -> Context {
+        Context::new(Box::new(command.clone()), ArgMatches::new(), App::new())
+    }
+
+    fn wrap(log: &Log, name: &'static str) -> Arc<dyn Middleware> {
+        let log = log.clone();
+        Arc::new(move |ctx: &Context, next: Next<'_>| {
+            log.lock().unwrap().push(format!("enter {name}"));
+            let result = next.run(ctx);
+            log.lock().unwrap().push(format!("leave {name}"));
+            result
+        })
+    }
+
+    fn record(log: &Log, name: &'static str) -> impl Fn(&Context) -> Result {
+        let log = log.clone();
+        move |_ctx: &Context| {
+            log.lock().unwrap().push(name.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn chain_order() {
+        let log = Log::default();
+        let command = Deploy(log.clone(), true);
+        let middleware = [wrap(&log, "outer"), wrap(&log, "inner")];
+
+        assert!(Next::new(&middleware, &command)
+            .run(&context(&command))
+            .is_ok());
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "enter outer",
+                "enter inner",
+                "deploy",
+                "leave inner",
+                "leave outer"
+            ]
+        );
+    }
+
+    #[test]
+    fn short_circuit() {
+        let log = Log::default();
+        let command = Deploy(log.clone(), true);
+        let middleware: [Arc<dyn Middleware>; 2] = [
+            Arc::new(before(|_ctx: &Context| {
+                Err(Error::Custom("missing token".to_string()))
+            })),
+            wrap(&log, "inner"),
+        ];
+
+        assert_eq!(
+            Next::new(&middleware, &command).run(&context(&command)),
+            Err(Error::Custom("missing token".to_string()))
+        );
+        assert!(log.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn hooks() {
+        let log = Log::default();
+        let middleware: [Arc<dyn Middleware>; 3] = [
+            Arc::new(before(record(&log, "before"))),
+            Arc::new(after(record(&log, "after"))),
+            Arc::new(on_error(|_ctx: &Context, err: Error| {
+                assert_eq!(err, Error::Custom("deploy failed".to_string()));
+                Ok(())
+            })),
+        ];
+
+        let command = Deploy(log.clone(), true);
+        assert!(Next::new(&middleware, &command)
+            .run(&context(&command))
+            .is_ok());
+        assert_eq!(*log.lock().unwrap(), ["before", "deploy", "after"]);
+
+        log.lock().unwrap().clear();
+        let command = Deploy(log.clone(), false);
+        assert!(Next::new(&middleware, &command)
+            .run(&context(&command))
+            .is_ok());
+        assert_eq!(*log.lock().unwrap(), ["before", "deploy", "after"]);
+
+        log.lock().unwrap().clear();
+        let middleware = &middleware[..2];
+        assert_eq!(
+            Next::new(middleware, &command).run(&context(&command)),
+            Err(Error::Custom("deploy failed".to_string()))
+        );
+        assert_eq!(*log.lock().unwrap(), ["before", "deploy"]);
+    }
+
+    #[test]
+    fn panics() {
+        let seen = Log::default();
+        let hook_seen = seen.clone();
+        let middleware: [Arc<dyn Middleware>; 1] =
+            [Arc::new(on_error(move |_ctx: &Context, err: Error| {
+                hook_seen.lock().unwrap().push(err.to_string());
+                Err(err)
+            }))];
+
+        let result = Next::new(&middleware, &Crash).run(&context(&Crash));
+        assert_eq!(
+            result,
+            Err(Error::Panic {
+                command: "crash".to_string(),
+                message: "disk 3 is on fire".to_string(),
+            })
+        );
+        assert_eq!(result.unwrap_err().exit_code(), 101);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            ["command 'crash' panicked: disk 3 is on fire"]
+        );
+    }
+}
@@ -3,10 +3,34 @@
 //! Contains all the important and main features of Dock
 
 pub mod app;
+pub mod arg;
+pub mod color;
 pub mod command;
 pub mod config;
 pub mod context;
+pub mod error;
+pub mod external;
+#[cfg(feature = "formats")]
+pub mod format;
 pub mod help;
+#[cfg(feature = "logging")]
+pub mod logging;
+pub mod middleware;
+pub mod output;
+pub mod pager;
 pub mod parser;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+pub mod progress;
+pub mod prompt;
+pub mod repl;
+pub mod script;
+pub mod subcommand;
+pub mod table;
+pub mod terminal;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod theme;
+pub mod usage;
 
 pub use app::App;
@@ -0,0 +1,223 @@
+//! Part of the Dock application that handles where output is written
+//!
+//! Output is written to the standard streams of the process unless it is captured, such as by the test harness,
+//! in which case it is kept in memory and can be read back. Commands write through [`Context::out`] and
+//! [`Context::err`](crate::context::Context::err), which strip colors when they are disabled and silence the
+//! standard output stream in quiet mode.
+//!
+//! [`Context::out`]: crate::context::Context::out
+
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crate::color::Stream;
+
+/// Identifier of the global flag enabling quiet mode, in which the standard output of commands is discarded
+pub const QUIET: &str = "quiet";
+
+/// The destination of the output written by the application and its commands
+#[derive(Debug, Clone, Default)]
+pub struct Output {
+    capture: Option<Capture>,
+}
+
+/// Buffers holding captured output
+#[derive(Debug, Clone, Default)]
+struct Capture {
+    stdout: Arc<Mutex<Vec<u8>>>,
+    stderr: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Capture {
+    fn buffer(&self, stream: Stream) -> &Arc<Mutex<Vec<u8>>> {
+        match stream {
+            Stream::Stdout => &self.stdout,
+            Stream::Stderr => &self.stderr,
+        }
+    }
+}
+
+impl Output {
+    /// Construct an output writing to the standard streams of the process
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Construct an output keeping everything written to it in memory
+    #[must_use]
+    pub fn captured() -> Self {
+        Self {
+            capture: Some(Capture::default()),
+        }
+    }
+
+    /// Whether the output is kept in memory rather than written to the process streams
+    #[must_use]
+    pub fn is_captured(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    /// Whether the stream is connected to a terminal, which is never the case for captured output
+    #[must_use]
+    pub fn is_terminal(&self, stream: Stream) -> bool {
+        !self.is_captured() && stream.is_terminal()
+    }
+
+    /// Get a writer for the stream
+    #[must_use]
+    pub fn writer(&self, stream: Stream) -> Writer {
+        Writer {
+            stream,
+            buffer: self
+                .capture
+                .as_ref()
+                .map(|capture| Arc::clone(capture.buffer(stream))),
+            strip: false,
+            escape: false,
+            discard: false,
+        }
+    }
+
+    /// Get the text captured from the stream so far, which is empty if the output is not captured
+    #[must_use]
+    pub fn captured_text(&self, stream: Stream) -> String {
+        self.capture
+            .as_ref()
+            .map(|capture| {
+                let buffer = capture
+                    .buffer(stream)
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                String::from_utf8_lossy(&buffer).to_string()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Writes to a process stream or to its captured buffer
+#[derive(Debug)]
+pub struct Writer {
+    stream: Stream,
+    buffer: Option<Arc<Mutex<Vec<u8>>>>,
+    /// Whether ANSI escape sequences are removed from the written text
+    strip: bool,
+    /// Whether the previous write ended inside an escape sequence
+    escape: bool,
+    /// Whether the written text is thrown away
+    discard: bool,
+}
+
+impl Writer {
+    /// The stream written to
+    #[must_use]
+    pub fn stream(&self) -> Stream {
+        self.stream
+    }
+
+    /// Property setter
+    ///
+    /// Sets whether ANSI escape sequences such as colors are removed from the written text
+    #[must_use]
+    pub fn set_strip_ansi(mut self, strip: bool) -> Self {
+        self.strip = strip;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets whether the written text is thrown away
+    #[must_use]
+    pub fn set_discard(mut self, discard: bool) -> Self {
+        self.discard = discard;
+        self
+    }
+
+    fn write_raw(&self, buf: &[u8]) -> io::Result<()> {
+        match (&self.buffer, self.stream) {
+            (Some(buffer), _) => {
+                buffer
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .extend_from_slice(buf);
+                Ok(())
+            }
+            (None, Stream::Stdout) => io::stdout().write_all(buf),
+            (None, Stream::Stderr) => io::stderr().write_all(buf),
+        }
+    }
+}
+
+impl Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.discard {
+            return Ok(buf.len());
+        }
+        if !self.strip {
+            self.write_raw(buf)?;
+            return Ok(buf.len());
+        }
+
+        let mut text = Vec::with_capacity(buf.len());
+        for &byte in buf {
+            if self.escape {
+                // The escape sequence ends with its final letter
+                self.escape = !byte.is_ascii_alphabetic();
+            } else if byte == 0x1b {
+                self.escape = true;
+            } else {
+                text.push(byte);
+            }
+        }
+
+        self.write_raw(&text)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match (&self.buffer, self.stream) {
+            (Some(_), _) => Ok(()),
+            (None, Stream::Stdout) => io::stdout().flush(),
+            (None, Stream::Stderr) => io::stderr().flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use super::*;
+
+    #[test]
+    fn captured_output() {
+        let output = Output::captured();
+
+        write!(output.writer(Stream::Stdout), "out").unwrap();
+        writeln!(output.clone().writer(Stream::Stdout), "put").unwrap();
+        writeln!(output.writer(Stream::Stderr), "err").unwrap();
+
+        assert_eq!(output.captured_text(Stream::Stdout), "output\n");
+        assert_eq!(output.captured_text(Stream::Stderr), "err\n");
+        assert!(!output.is_terminal(Stream::Stdout));
+        assert_eq!(Output::new().captured_text(Stream::Stdout), "");
+    }
+
+    #[test]
+    fn writer_filters() {
+        let output = Output::captured();
+        let painted = crate::Color::Red.paint("red").to_string();
+
+        let mut writer = output.writer(Stream::Stdout).set_strip_ansi(true);
+        write!(writer, "{painted} ").unwrap();
+        // Escape sequences split across writes are removed too
+        writer.write_all(&painted.as_bytes()[..3]).unwrap();
+        writer.write_all(&painted.as_bytes()[3..]).unwrap();
+
+        writeln!(output.writer(Stream::Stderr).set_discard(true), "hidden").unwrap();
+
+        assert_eq!(output.captured_text(Stream::Stdout), "red red");
+        assert_eq!(output.captured_text(Stream::Stderr), "");
+    }
+}
@@ -0,0 +1,125 @@
+//! Part of the Dock application that pipes long output through a pager
+//!
+//! Paging is opt-in through `App::set_pager`. Output is only paged when the standard output stream is a terminal,
+//! the output is taller than the terminal and the `DOCK_NO_PAGER` environment variable is not set.
+//! The pager is read from the `PAGER` environment variable and falls back to `less -R`.
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use crate::{color::Stream, context::Environment, output::Output, terminal};
+
+/// Environment variable disabling the pager when set to a non-empty value
+pub const NO_PAGER: &str = "DOCK_NO_PAGER";
+
+/// Pager used when the `PAGER` environment variable is not set
+pub const DEFAULT_PAGER: &str = "less -R";
+
+/// Print the text to the standard output stream of the output, through the pager if the text does not fit the
+/// terminal
+///
+/// The pager and its options are read from the environment the application is run in.
+pub fn page(text: &str, env: &Environment, output: &Output) {
+    let var = |name: &str| env.env_var(name).map(str::to_string);
+
+    if should_page(
+        text,
+        var,
+        || output.is_terminal(Stream::Stdout),
+        terminal::height(env, output),
+    ) {
+        if let Some(command) = pager(var) {
+            if spawn(&command, text, env).is_ok() {
+                return;
+            }
+        }
+    }
+
+    let _ = writeln!(output.writer(Stream::Stdout), "{text}");
+}
+
+/// Whether the text should be paged given the environment lookup, terminal check and terminal height
+pub(crate) fn should_page(
+    text: &str,
+    var: impl Fn(&str) -> Option<String>,
+    is_terminal: impl FnOnce() -> bool,
+    height: Option<usize>,
+) -> bool {
+    if var(NO_PAGER).is_some_and(|value| !value.is_empty()) || !is_terminal() {
+        return false;
+    }
+
+    height.is_some_and(|height| text.lines().count() >= height)
+}
+
+/// Get the pager program and its arguments given the environment lookup
+///
+/// Returns `None` if the `PAGER` variable cannot be split into arguments.
+pub(crate) fn pager(var: impl Fn(&str) -> Option<String>) -> Option<Vec<String>> {
+    let command = var("PAGER")
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string());
+
+    shlex::split(&command).filter(|split| !split.is_empty())
+}
+
+fn spawn(command: &[String], text: &str, env: &Environment) -> std::io::Result<()> {
+    let mut pager = Command::new(&command[0]);
+    pager.args(&command[1..]).stdin(Stdio::piped());
+
+    // Keep colors and quit immediately if the text fits after all, like git does
+    if env.env_var("LESS").is_none() {
+        pager.env("LESS", "FRX");
+    }
+
+    let mut child = pager.spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager closes its input when the user quits before reaching the end
+        let _ = writeln!(stdin, "{text}");
+    }
+
+    child.wait().map(|_| ())
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use super::*;
+
+    fn lookup<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value).to_string())
+        }
+    }
+
+    #[test]
+    fn pager_choice() {
+        let long = "line\n".repeat(30);
+
+        assert!(should_page(&long, lookup(&[]), || true, Some(24)));
+        assert!(!should_page("short", lookup(&[]), || true, Some(24)));
+        assert!(!should_page(&long, lookup(&[]), || false, Some(24)));
+        assert!(!should_page(&long, lookup(&[]), || true, None));
+        assert!(!should_page(
+            &long,
+            lookup(&[(NO_PAGER, "1")]),
+            || true,
+            Some(24)
+        ));
+
+        assert_eq!(
+            pager(lookup(&[])),
+            Some(vec!["less".to_string(), "-R".to_string()])
+        );
+        assert_eq!(
+            pager(lookup(&[("PAGER", "most -s")])),
+            Some(vec!["most".to_string(), "-s".to_string()])
+        );
+        assert_eq!(pager(lookup(&[("PAGER", "")])), pager(lookup(&[])));
+    }
+}
@@ -1,5 +1,13 @@
 //! Part of the Dock application that handles all parser logic
 
+use std::{borrow::Cow, ffi::OsString};
+
+use crate::{
+    arg::{Arg, ArgMatches},
+    context::Environment,
+    error::{Error, Result},
+};
+
 /// Represents a part of the  raw input
 #[derive(Debug, PartialEq, Eq)]
 pub(crate) enum InputPart {
@@ -13,9 +21,10 @@ pub(crate) enum InputPart {
 /// Rppresents the raw input
 #[allow(dead_code)]
 pub(crate) struct Input {
-    raw: String,
     split: Vec<String>,
     lexed: Vec<InputPart>,
+    /// The split arguments as they were passed, which may not be valid unicode
+    os_split: Vec<OsString>,
 }
 
 impl Input {
@@ -30,24 +39,339 @@ impl Input {
         }
     }
 
-    pub fn new(raw: &str) -> Self {
-        // TODO: handle this properly
-        let split = shlex::split(raw).expect("Failed to parse commands");
-
+    fn lex(split: &[String]) -> Vec<InputPart> {
         let mut lexed = vec![];
 
-        let mut split_i = split.clone().into_iter();
+        let mut split_i = split.iter();
 
-        lexed.push(InputPart::Program(split_i.next().unwrap()));
+        if let Some(program) = split_i.next() {
+            lexed.push(InputPart::Program(program.clone()));
+        }
 
         for item in split_i {
-            lexed.push(Self::get_part(&item));
+            lexed.push(Self::get_part(item));
         }
 
+        lexed
+    }
+
+    /// Split a raw command line such as `tool build "my target"` into arguments
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Custom`] if the line holds unbalanced quotes.
+    pub fn new(raw: &str) -> Result<Self> {
+        let split = shlex::split(raw)
+            .ok_or_else(|| Error::Custom(format!("unbalanced quotes in '{raw}'")))?;
+
+        Ok(Self {
+            lexed: Self::lex(&split),
+            os_split: split.iter().map(OsString::from).collect(),
+            split,
+        })
+    }
+
+    /// Construct the input from already split arguments such as `std::env::args_os`
+    ///
+    /// Arguments that are not valid unicode are parsed with their invalid sequences replaced, while
+    /// [`Input::os_args`] keeps them as they were passed.
+    pub fn from_args<I, T>(args: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        let os_split = args.into_iter().map(Into::into).collect::<Vec<OsString>>();
+        let split = os_split
+            .iter()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect::<Vec<String>>();
+
         Self {
-            raw: raw.to_string(),
+            lexed: Self::lex(&split),
             split,
-            lexed,
+            os_split,
+        }
+    }
+
+    /// The program name the input was invoked with
+    pub fn program(&self) -> Option<&str> {
+        self.split.first().map(String::as_str)
+    }
+
+    /// The raw arguments following the program name
+    pub fn args(&self) -> &[String] {
+        self.split.get(1..).unwrap_or_default()
+    }
+
+    /// The arguments following the program name as they were passed
+    pub fn os_args(&self) -> &[OsString] {
+        self.os_split.get(1..).unwrap_or_default()
+    }
+}
+
+/// Quote an argument so that splitting it the way a shell would gives it back
+///
+/// # Errors
+///
+/// Returns an [`Error::Custom`] if the argument holds a nul byte, which cannot be quoted.
+pub(crate) fn quote(arg: &str) -> Result<Cow<'_, str>> {
+    shlex::try_quote(arg)
+        .map_err(|_| Error::Custom(format!("cannot quote '{}'", arg.escape_debug())))
+}
+
+/// Parses raw arguments against the spec of a command
+pub(crate) struct Parser<'a> {
+    spec: &'a [Arg],
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(spec: &'a [Arg]) -> Self {
+        Self { spec }
+    }
+
+    fn find_long(&self, long: &str) -> Result<&'a Arg> {
+        self.spec
+            .iter()
+            .find(|arg| {
+                arg.long.as_deref() == Some(long) || arg.aliases.iter().any(|alias| alias == long)
+            })
+            .ok_or_else(|| Error::UnknownArgument(format!("--{long}")))
+    }
+
+    fn find_short(&self, short: char) -> Option<&'a Arg> {
+        self.spec.iter().find(|arg| arg.short == Some(short))
+    }
+
+    /// Parse the arguments into matches, applying environment variables, defaults and required checks
+    #[allow(dead_code)]
+    pub fn parse(&self, args: &[String], env: &Environment) -> Result<ArgMatches> {
+        let mut matches = ArgMatches::new();
+        self.parse_into(args, &mut matches)?;
+        self.resolve(&mut matches, env)?;
+
+        Ok(matches)
+    }
+
+    /// Parse the arguments into existing matches
+    ///
+    /// Environment variables, defaults and required checks are applied separately by [`Parser::resolve`].
+    pub fn parse_into(&self, args: &[String], matches: &mut ArgMatches) -> Result {
+        let positionals = self.parse_named(args, matches)?;
+        self.assign_positionals(positionals, matches)
+    }
+
+    /// Match the named arguments preceding the command name
+    ///
+    /// Returns the command name along with the arguments following it, or `None` if no command name was passed.
+    pub fn parse_command<'b>(
+        &self,
+        args: &'b [String],
+        matches: &mut ArgMatches,
+    ) -> Result<Option<(&'b str, &'b [String])>> {
+        let mut i = 0;
+
+        while let Some(token) = args.get(i) {
+            if token == "--" {
+                i += 1;
+                break;
+            } else if self.is_named(token) {
+                i = self.parse_option(args, i, matches)?;
+            } else {
+                break;
+            }
+        }
+
+        Ok(args.get(i).map(|name| (name.as_str(), &args[i + 1..])))
+    }
+
+    /// Match the named arguments and return the positional values in order
+    fn parse_named(&self, args: &[String], matches: &mut ArgMatches) -> Result<Vec<String>> {
+        let mut positionals = vec![];
+        let mut i = 0;
+
+        while let Some(token) = args.get(i) {
+            if token == "--" {
+                positionals.extend_from_slice(&args[i + 1..]);
+                break;
+            } else if self.is_named(token) {
+                i = self.parse_option(args, i, matches)?;
+            } else {
+                positionals.push(token.clone());
+                i += 1;
+            }
+        }
+
+        Ok(positionals)
+    }
+
+    /// Whether the token is a short or long argument rather than a positional value
+    fn is_named(&self, token: &str) -> bool {
+        token.len() > 1 && token.starts_with('-') && !self.is_negative_number(token)
+    }
+
+    /// Match the named argument at index `i` and return the index of the next token
+    fn parse_option(&self, args: &[String], i: usize, matches: &mut ArgMatches) -> Result<usize> {
+        let token = &args[i];
+        let mut next = i + 1;
+
+        if let Some(long) = token.strip_prefix("--") {
+            let (long, inline) = match long.split_once('=') {
+                Some((long, value)) => (long, Some(value.to_string())),
+                None => (long, None),
+            };
+            let arg = self.find_long(long)?;
+
+            if arg.takes_value {
+                let value = if let Some(value) = inline {
+                    value
+                } else {
+                    next += 1;
+                    args.get(i + 1)
+                        .cloned()
+                        .ok_or_else(|| Error::MissingValue(arg.display_name()))?
+                };
+                matches.add_value(&arg.id, &value);
+            } else if inline.is_some() {
+                return Err(Error::UnexpectedValue(token.clone()));
+            } else {
+                matches.add_occurrence(&arg.id);
+            }
+        } else {
+            let shorts = &token[1..];
+
+            for (j, short) in shorts.char_indices() {
+                let arg = self
+                    .find_short(short)
+                    .ok_or_else(|| Error::UnknownArgument(format!("-{short}")))?;
+
+                if arg.takes_value {
+                    let rest = &shorts[j + short.len_utf8()..];
+                    let value = if rest.is_empty() {
+                        next += 1;
+                        args.get(i + 1)
+                            .cloned()
+                            .ok_or_else(|| Error::MissingValue(arg.display_name()))?
+                    } else {
+                        rest.to_string()
+                    };
+                    matches.add_value(&arg.id, &value);
+                    break;
+                }
+
+                matches.add_occurrence(&arg.id);
+            }
+        }
+
+        Ok(next)
+    }
+
+    /// Whether the token is a negative number such as `-3`, `-0.5` or `-1e3`, unless it starts with a short argument
+    fn is_negative_number(&self, token: &str) -> bool {
+        let Some(number) = token.strip_prefix('-') else {
+            return false;
+        };
+        let (mantissa, exponent) = match number.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+            None => (number, None),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+
+        !(integer.is_empty() && fraction.is_empty())
+            && digits(integer)
+            && digits(fraction)
+            && exponent.is_none_or(|exponent| {
+                let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+                !exponent.is_empty() && digits(exponent)
+            })
+            && token
+                .chars()
+                .nth(1)
+                .is_none_or(|short| self.find_short(short).is_none())
+    }
+
+    fn assign_positionals(&self, positionals: Vec<String>, matches: &mut ArgMatches) -> Result {
+        let mut positionals = positionals.into_iter();
+
+        for arg in self.spec.iter().filter(|arg| arg.is_positional()) {
+            if arg.multiple {
+                for value in positionals.by_ref() {
+                    matches.add_value(&arg.id, &value);
+                }
+            } else if let Some(value) = positionals.next() {
+                matches.add_value(&arg.id, &value);
+            }
+        }
+
+        match positionals.next() {
+            Some(value) => Err(Error::UnexpectedValue(value)),
+            None => Ok(()),
+        }
+    }
+
+    /// Apply environment variables and defaults to the arguments that were not passed, check required ones and
+    /// validate the values
+    pub fn resolve(&self, matches: &mut ArgMatches, env: &Environment) -> Result {
+        self.apply_defaults(matches, env);
+
+        if let Some(arg) = self.missing(matches).first() {
+            return Err(Error::MissingArgument(arg.display_name()));
+        }
+
+        self.validate(matches)
+    }
+
+    /// Get the required arguments that are still absent
+    pub fn missing(&self, matches: &ArgMatches) -> Vec<&'a Arg> {
+        self.spec
+            .iter()
+            .filter(|arg| arg.required && !matches.is_present(&arg.id))
+            .collect()
+    }
+
+    /// Run the validators of the arguments on their values
+    fn validate(&self, matches: &ArgMatches) -> Result {
+        for arg in self.spec {
+            let Some(validator) = arg.validator else {
+                continue;
+            };
+
+            for value in matches.values_of(&arg.id) {
+                validator(value).map_err(|reason| Error::InvalidValue {
+                    arg: arg.id.clone(),
+                    value: value.clone(),
+                    reason,
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply environment variables and defaults to the arguments that were not passed
+    pub fn apply_defaults(&self, matches: &mut ArgMatches, env: &Environment) {
+        for arg in self.spec {
+            if matches.is_present(&arg.id) {
+                continue;
+            }
+
+            let env = arg
+                .env
+                .as_ref()
+                .and_then(|name| env.env_var(name))
+                .filter(|value| !value.is_empty());
+
+            match (env, &arg.default) {
+                (Some(value), _) if arg.takes_value => matches.add_value(&arg.id, value),
+                (Some(value), _) => {
+                    if !matches!(value, "0" | "false" | "no") {
+                        matches.add_occurrence(&arg.id);
+                    }
+                }
+                // A flag holds no value, so a default would make it present on every invocation
+                (None, Some(default)) if arg.takes_value => matches.add_value(&arg.id, default),
+                (None, _) => {}
+            }
         }
     }
 }
@@ -71,7 +395,21 @@ mod app_tests {
                 "--string",
                 "This is a string with spaces"
             ],
-            Input::new(&buf).split
+            Input::new(&buf).unwrap().split
+        );
+        assert!(Input::new(r#"dock command "unbalanced"#).is_err());
+    }
+
+    #[test]
+    fn quoted_args() {
+        let input = Input::from_args(["dock", "command", "my target", "nul\0byte"]);
+        assert_eq!(input.args(), ["command", "my target", "nul\0byte"]);
+
+        let line = format!("{} command", quote("my tool").unwrap());
+        assert_eq!(Input::new(&line).unwrap().split, ["my tool", "command"]);
+        assert_eq!(
+            quote("nul\0byte").err(),
+            Some(Error::Custom("cannot quote 'nul\\0byte'".to_string()))
         );
     }
 
@@ -89,7 +427,111 @@ mod app_tests {
                 InputPart::LongArg("--string".to_string()),
                 InputPart::String("This is a string with spaces".to_string())
             ],
-            Input::new(&buf).lexed
+            Input::new(&buf).unwrap().lexed
         );
     }
+
+    #[test]
+    fn parse_spec() {
+        let spec = vec![
+            Arg::new("release").set_short('r').set_takes_value(false),
+            Arg::new("jobs").set_short('j').set_long("jobs"),
+            Arg::new("target").set_required(true).set_env("TARGET"),
+            Arg::new("files").set_multiple(true),
+        ];
+        let args = ["-rj4", "x86", "--jobs=8", "a.rs", "--", "-b.rs"]
+            .map(String::from)
+            .to_vec();
+        let env = Environment::new();
+
+        let matches = Parser::new(&spec).parse(&args, &env).unwrap();
+
+        assert!(matches.is_present("release"));
+        assert_eq!(matches.value_of("jobs"), Some("8"));
+        assert_eq!(matches.value_of("target"), Some("x86"));
+        assert_eq!(matches.values_of("files"), ["a.rs", "-b.rs"]);
+
+        assert_eq!(
+            Parser::new(&spec).parse(&["--unknown".to_string()], &env),
+            Err(Error::UnknownArgument("--unknown".to_string()))
+        );
+        assert_eq!(
+            Parser::new(&spec).parse(&["-r".to_string()], &env),
+            Err(Error::MissingArgument("<TARGET>".to_string()))
+        );
+
+        let env = env.set_var("TARGET", "wasm");
+        let matches = Parser::new(&spec).parse(&[], &env).unwrap();
+        assert_eq!(matches.value_of("target"), Some("wasm"));
+    }
+
+    #[test]
+    fn flag_defaults() {
+        let spec = vec![
+            Arg::new("verbose")
+                .set_long("verbose")
+                .set_takes_value(false)
+                .set_default("false"),
+            Arg::new("jobs").set_long("jobs").set_default("4"),
+        ];
+        let matches = Parser::new(&spec).parse(&[], &Environment::new()).unwrap();
+
+        assert!(!matches.is_present("verbose"));
+        assert_eq!(matches.value_of("jobs"), Some("4"));
+    }
+
+    #[test]
+    fn negative_numbers() {
+        let spec = vec![
+            Arg::new("offset"),
+            Arg::new("max").set_short('9').set_takes_value(false),
+        ];
+        let parse =
+            |token: &str| Parser::new(&spec).parse(&[token.to_string()], &Environment::new());
+
+        for number in ["-3", "-0.5", "-.5", "-5.", "-1e3", "-2.5E-4", "-7e+2"] {
+            assert_eq!(parse(number).unwrap().value_of("offset"), Some(number));
+        }
+        for (token, short) in [
+            ("-inf", "-i"),
+            ("-nan", "-n"),
+            ("-.", "-."),
+            ("-1.2.3", "-1"),
+            ("-1e", "-1"),
+            ("-e3", "-e"),
+        ] {
+            assert_eq!(
+                parse(token).err(),
+                Some(Error::UnknownArgument(short.to_string()))
+            );
+        }
+        assert!(parse("-9").unwrap().is_present("max"));
+    }
+
+    #[test]
+    fn parse_command() {
+        let globals = vec![
+            Arg::new("verbose").set_short('v').set_takes_value(false),
+            Arg::new("config").set_long("config"),
+        ];
+        let args = ["-v", "--config", "dock.toml", "build", "-v"]
+            .map(String::from)
+            .to_vec();
+        let mut matches = ArgMatches::new();
+
+        let (name, rest) = Parser::new(&globals)
+            .parse_command(&args, &mut matches)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(name, "build");
+        assert_eq!(rest, ["-v"]);
+        assert_eq!(matches.value_of("config"), Some("dock.toml"));
+
+        Parser::new(&globals)
+            .parse_into(rest, &mut matches)
+            .unwrap();
+
+        assert_eq!(matches.occurrences("verbose"), 2);
+    }
 }
@@ -0,0 +1,379 @@
+//! Part of the Dock application that loads commands from plugins built as shared libraries
+//!
+//! This module is enabled by the `plugins` feature. A plugin is a crate built as a `cdylib` which depends on the same
+//! version of Dock, is built with the same compiler as the application and declares its commands with
+//! [`export_plugin!`](crate::export_plugin):
+//!
+//! ```rs,no_run
+//! #[command]
+//! fn deploy(args: DeployArgs) {
+//!     // --snip--
+//! }
+//!
+//! fn register(registrar: &mut Registrar) {
+//!     registrar.register_command(deploy);
+//! }
+//!
+//! dock::export_plugin!(register);
+//! ```
+//!
+//! The application loads every plugin of a directory at startup once [`App::set_plugin_dir`] is called. Plugins built
+//! for another plugin ABI, with another compiler, or against another version or feature set of Dock are skipped with
+//! a warning, and the commands of a plugin are grouped in the help message under its name, version and description
+//! unless they set their own category.
+//!
+//! [`App::set_plugin_dir`]: crate::App::set_plugin_dir
+
+use std::{
+    ffi::{c_char, CStr},
+    fmt::{self, Debug},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use libloading::Library;
+
+use crate::{
+    arg::Arg,
+    command::{Command, Example},
+    context::Context,
+    error::{Error, Result},
+};
+
+/// Version of the layout of [`PluginDeclaration`], incremented whenever it changes
+pub const ABI_VERSION: u32 = 1;
+/// Version of the compiler Dock is built with, followed by a nul byte, which plugins must be built with as Rust has
+/// no stable ABI
+pub const RUSTC_VERSION: &str = concat!(env!("DOCK_RUSTC_VERSION"), "\0");
+/// Version of Dock, which plugins must be built against as commands are passed as trait objects
+pub const DOCK_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Features Dock is built with, which plugins must enable as they change the layout of the types commands receive
+pub const DOCK_FEATURES: &str = env!("DOCK_FEATURES");
+/// Name of the static holding the declaration of a plugin
+pub const SYMBOL: &str = "DOCK_PLUGIN";
+
+/// Describes a plugin and registers its commands, exported by [`export_plugin!`](crate::export_plugin)
+///
+/// Only the plugin ABI and the compiler version are laid out for C, so that they can be read from a plugin built with
+/// any compiler. The rest of the declaration uses Rust types and is only read once both match.
+#[repr(C)]
+pub struct PluginDeclaration {
+    /// Plugin ABI the plugin was built for
+    pub abi_version: u32,
+    /// Nul-terminated version of the compiler the plugin was built with
+    pub rustc_version: *const c_char,
+    /// Version of Dock the plugin was built against
+    pub dock_version: &'static str,
+    /// Features of Dock the plugin was built with
+    pub dock_features: &'static str,
+    /// Name of the plugin
+    pub name: &'static str,
+    /// Version of the plugin
+    pub version: &'static str,
+    /// Description of the plugin
+    pub description: &'static str,
+    /// Registers the commands of the plugin
+    pub register: fn(&mut Registrar),
+}
+
+// SAFETY: the compiler version points to a string literal, which is never written to
+unsafe impl Sync for PluginDeclaration {}
+
+/// Declare the plugin built from the crate, taking the function registering its commands
+///
+/// The name, version and description of the plugin are those of the crate.
+///
+/// ```rs,no_run
+/// dock::export_plugin!(|registrar| registrar.register_command(deploy));
+/// ```
+#[macro_export]
+macro_rules! export_plugin {
+    ($register:expr) => {
+        #[no_mangle]
+        pub static DOCK_PLUGIN: $crate::plugin::PluginDeclaration =
+            $crate::plugin::PluginDeclaration {
+                abi_version: $crate::plugin::ABI_VERSION,
+                rustc_version: $crate::plugin::RUSTC_VERSION.as_ptr().cast(),
+                dock_version: $crate::plugin::DOCK_VERSION,
+                dock_features: $crate::plugin::DOCK_FEATURES,
+                name: env!("CARGO_PKG_NAME"),
+                version: env!("CARGO_PKG_VERSION"),
+                description: env!("CARGO_PKG_DESCRIPTION"),
+                register: $register,
+            };
+    };
+}
+
+/// Collects the commands registered by a plugin
+#[derive(Default)]
+pub struct Registrar {
+    commands: Vec<Box<dyn Command>>,
+}
+
+impl Registrar {
+    /// Register a command provided by the plugin
+    pub fn register_command(&mut self, command: impl Command + 'static) {
+        self.commands.push(Box::new(command));
+    }
+}
+
+/// A plugin loaded from a shared library
+pub struct Plugin {
+    /// Name of the plugin
+    pub name: String,
+    /// Version of the plugin
+    pub version: String,
+    /// Description of the plugin
+    pub description: String,
+    /// Path of the shared library
+    pub path: PathBuf,
+    register: fn(&mut Registrar),
+    // Kept loaded as long as a command of the plugin is alive, since their code lives in it
+    _library: Library,
+}
+
+impl Debug for Plugin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Plugin")
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Plugin {
+    /// Load the plugin from the shared library at the path
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Custom`] if the library cannot be loaded, does not declare a plugin, or was built for
+    /// another plugin ABI, with another compiler, or against another version or feature set of Dock.
+    pub fn load(path: &Path) -> Result<Self> {
+        let failed = |reason: String| {
+            Error::Custom(format!(
+                "failed to load plugin '{}': {reason}",
+                path.display()
+            ))
+        };
+
+        // SAFETY: loading a library runs its initializers, which is the very trust placed in a plugin
+        let library = unsafe { Library::new(path) }.map_err(|err| failed(err.to_string()))?;
+
+        // SAFETY: the symbol is a static declared by `export_plugin!`, whose C fields are checked before reading
+        // anything else, and the reference does not outlive the library as the fields are copied
+        let declaration = unsafe {
+            let symbol = library
+                .get::<*const PluginDeclaration>(SYMBOL.as_bytes())
+                .map_err(|_| failed(format!("no {SYMBOL} declaration")))?;
+            &**symbol
+        };
+        check(declaration).map_err(failed)?;
+
+        Ok(Self {
+            name: declaration.name.to_string(),
+            version: declaration.version.to_string(),
+            description: declaration.description.to_string(),
+            path: path.to_path_buf(),
+            register: declaration.register,
+            _library: library,
+        })
+    }
+
+    /// Get the commands registered by the plugin
+    #[must_use]
+    pub fn commands(self: &Arc<Self>) -> Vec<Box<dyn Command>> {
+        let mut registrar = Registrar::default();
+        (self.register)(&mut registrar);
+
+        registrar
+            .commands
+            .into_iter()
+            .map(|command| {
+                Box::new(PluginCommand {
+                    command,
+                    plugin: Arc::clone(self),
+                }) as Box<dyn Command>
+            })
+            .collect()
+    }
+}
+
+/// Check that the plugin was built for the plugin ABI, with the compiler and against the version and features of Dock
+/// of the application
+fn check(declaration: &PluginDeclaration) -> std::result::Result<(), String> {
+    if declaration.abi_version != ABI_VERSION {
+        return Err(format!(
+            "built for plugin ABI {}, expected {ABI_VERSION}",
+            declaration.abi_version
+        ));
+    }
+
+    let expected = RUSTC_VERSION.trim_end_matches('\0');
+    if declaration.rustc_version.is_null() {
+        return Err(format!(
+            "built with an unknown compiler, expected {expected}"
+        ));
+    }
+    // SAFETY: the pointer is not null and the ABI version guarantees it points to a nul-terminated string
+    let rustc_version = unsafe { CStr::from_ptr(declaration.rustc_version) }.to_string_lossy();
+    if rustc_version != expected {
+        return Err(format!("built with {rustc_version}, expected {expected}"));
+    }
+
+    if declaration.dock_version != DOCK_VERSION {
+        return Err(format!(
+            "built against dock {}, expected {DOCK_VERSION}",
+            declaration.dock_version
+        ));
+    }
+    if declaration.dock_features != DOCK_FEATURES {
+        return Err(format!(
+            "built with dock features '{}', expected '{DOCK_FEATURES}'",
+            declaration.dock_features
+        ));
+    }
+
+    Ok(())
+}
+
+/// Find the shared libraries in the directory, sorted by path
+///
+/// A directory that does not exist holds no plugins.
+#[must_use]
+pub fn discover(dir: &Path) -> Vec<PathBuf> {
+    let mut paths = dir
+        .read_dir()
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.is_file()
+                        && path
+                            .file_name()
+                            .and_then(|name| name.to_str())
+                            .is_some_and(|name| name.ends_with(std::env::consts::DLL_SUFFIX))
+                })
+                .collect::<Vec<PathBuf>>()
+        })
+        .unwrap_or_default();
+
+    paths.sort();
+    paths
+}
+
+/// A command registered by a plugin, which keeps the plugin loaded
+#[derive(Clone)]
+struct PluginCommand {
+    // Declared first so that it is dropped before the library holding its code
+    command: Box<dyn Command>,
+    plugin: Arc<Plugin>,
+}
+
+impl Command for PluginCommand {
+    fn name(&self) -> String {
+        self.command.name()
+    }
+
+    fn description(&self) -> String {
+        self.command.description()
+    }
+
+    fn disabled(&self) -> bool {
+        self.command.disabled()
+    }
+
+    fn args(&self) -> Vec<Arg> {
+        self.command.args()
+    }
+
+    fn category(&self) -> Option<String> {
+        self.command.category().or_else(|| {
+            let Plugin {
+                name,
+                version,
+                description,
+                ..
+            } = &*self.plugin;
+
+            Some(if description.is_empty() {
+                format!("{name} {version}")
+            } else {
+                format!("{name} {version} - {description}")
+            })
+        })
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        self.command.examples()
+    }
+
+    fn call(&self, ctx: &Context) -> Result {
+        self.command.call(ctx)
+    }
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use super::*;
+
+    fn declaration(abi_version: u32, dock_version: &'static str) -> PluginDeclaration {
+        PluginDeclaration {
+            abi_version,
+            rustc_version: RUSTC_VERSION.as_ptr().cast(),
+            dock_version,
+            dock_features: DOCK_FEATURES,
+            name: "deploy-tools",
+            version: "1.0.0",
+            description: "Deployment commands",
+            register: |_| (),
+        }
+    }
+
+    #[test]
+    fn version_check() {
+        assert!(check(&declaration(ABI_VERSION, DOCK_VERSION)).is_ok());
+        assert_eq!(
+            check(&declaration(ABI_VERSION + 1, DOCK_VERSION)),
+            Err(format!(
+                "built for plugin ABI {}, expected {ABI_VERSION}",
+                ABI_VERSION + 1
+            ))
+        );
+        assert!(check(&declaration(ABI_VERSION, "0.0.0-other")).is_err());
+
+        let other_rustc = PluginDeclaration {
+            rustc_version: c"rustc 1.0.0 (a59807a 2015-05-13)".as_ptr(),
+            ..declaration(ABI_VERSION, DOCK_VERSION)
+        };
+        assert!(check(&other_rustc)
+            .unwrap_err()
+            .starts_with("built with rustc 1.0.0 (a59807a 2015-05-13), expected rustc "));
+
+        let other_features = PluginDeclaration {
+            dock_features: "other",
+            ..declaration(ABI_VERSION, DOCK_VERSION)
+        };
+        assert!(check(&other_features).is_err());
+    }
+
+    #[test]
+    fn plugin_discovery() {
+        let dir = std::env::temp_dir().join(format!("dock-plugin-dir-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let library = dir.join(format!("libbroken{}", std::env::consts::DLL_SUFFIX));
+        std::fs::write(&library, "not a library").unwrap();
+        std::fs::write(dir.join("README.md"), "").unwrap();
+
+        assert_eq!(discover(&dir), vec![library.clone()]);
+        assert!(discover(&dir.join("missing")).is_empty());
+        assert!(Plugin::load(&library)
+            .unwrap_err()
+            .to_string()
+            .starts_with(&format!("failed to load plugin '{}'", library.display())));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
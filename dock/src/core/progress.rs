@@ -0,0 +1,520 @@
+//! Part of the Dock application that reports the progress of long-running commands
+//!
+//! Progress bars and spinners are created from the context with [`Context::progress`] and drawn on the standard
+//! error stream, so the standard output of a command can still be piped. Several bars can be shown at once, including
+//! from different threads, and are redrawn in place when the stream is a terminal. Elsewhere, such as in CI logs,
+//! every bar is reported as a line of text when it starts, at most once per interval while it runs and when it
+//! finishes, or is aborted by being dropped without finishing. Nothing is shown in quiet mode.
+//!
+//! ```rs,no_run
+//! let progress = ctx.progress();
+//! let bar = progress.bar("Downloading", files.len() as u64);
+//!
+//! for file in files {
+//!     download(file)?;
+//!     bar.inc(1);
+//! }
+//! bar.finish();
+//! ```
+//!
+//! [`Context::progress`]: crate::context::Context::progress
+
+use std::{
+    fmt::Write as _,
+    io::Write,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    color::Stream,
+    output::{Output, Writer},
+    table::truncate,
+    terminal::{self, display_width},
+    theme::Theme,
+};
+
+/// Interval between the lines reporting a bar when the standard error stream is not a terminal
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Minimum delay between two redraws on a terminal, so frequent updates do not flood it
+const REDRAW_DELAY: Duration = Duration::from_millis(50);
+/// Maximum number of columns taken by the filled part of a bar
+const MAX_BAR_WIDTH: usize = 40;
+/// Frames of the spinner animation
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Draws progress bars and spinners on the standard error stream
+///
+/// Cloning a `Progress` shares the display, so bars created from every clone are drawn together.
+#[derive(Clone)]
+pub struct Progress {
+    state: Arc<Mutex<State>>,
+}
+
+/// State of the display shared by every bar
+struct State {
+    /// Destination of the display
+    writer: Writer,
+    /// Whether nothing is shown
+    hidden: bool,
+    /// Whether bars are redrawn in place or reported as lines of text
+    terminal: bool,
+    /// Styles of the bars, if colors are enabled
+    theme: Option<Theme>,
+    /// Interval between the lines reporting a bar when not on a terminal
+    interval: Duration,
+    /// Number of columns available to a bar
+    width: usize,
+    /// Bars that are not finished, in the order they were created
+    bars: Vec<BarState>,
+    /// Identifier given to the next bar
+    next_id: usize,
+    /// Number of lines drawn on the terminal by the last redraw
+    drawn: usize,
+    /// When the terminal was last redrawn
+    last_draw: Option<Instant>,
+}
+
+/// State of a single bar or spinner
+struct BarState {
+    id: usize,
+    message: String,
+    position: u64,
+    /// Length of the bar, or `None` for a spinner
+    total: Option<u64>,
+    /// Current frame of the spinner animation
+    frame: usize,
+    /// When the bar was last reported as a line of text
+    last_log: Option<Instant>,
+}
+
+impl Progress {
+    /// Create a display drawing on the standard error stream of the output
+    pub(crate) fn new(output: &Output) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State {
+                writer: output.writer(Stream::Stderr),
+                hidden: false,
+                terminal: output.is_terminal(Stream::Stderr),
+                theme: None,
+                interval: DEFAULT_INTERVAL,
+                width: terminal::DEFAULT_WIDTH,
+                bars: vec![],
+                next_id: 0,
+                drawn: 0,
+                last_draw: None,
+            })),
+        }
+    }
+
+    /// Property setter
+    ///
+    /// Sets whether nothing is shown, such as in quiet mode
+    #[must_use]
+    pub fn set_hidden(self, hidden: bool) -> Self {
+        self.lock().hidden = hidden;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets whether bars are redrawn in place on a terminal or reported as lines of text
+    #[must_use]
+    pub fn set_terminal(self, terminal: bool) -> Self {
+        self.lock().terminal = terminal;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the styles of the bars, or `None` to draw them without colors
+    #[must_use]
+    pub fn set_theme(self, theme: Option<Theme>) -> Self {
+        self.lock().theme = theme;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the interval between the lines reporting a bar when not on a terminal
+    #[must_use]
+    pub fn set_interval(self, interval: Duration) -> Self {
+        self.lock().interval = interval;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the number of columns available to a bar, which defaults to 80 columns
+    #[must_use]
+    pub fn set_width(self, width: usize) -> Self {
+        self.lock().width = width;
+        self
+    }
+
+    /// Show a bar going from zero to `total`
+    #[must_use]
+    pub fn bar(&self, message: &str, total: u64) -> ProgressBar {
+        self.add(message, Some(total))
+    }
+
+    /// Show a spinner for work whose length is unknown
+    ///
+    /// The spinner advances on every update, or on its own with [`ProgressBar::enable_steady_tick`].
+    #[must_use]
+    pub fn spinner(&self, message: &str) -> ProgressBar {
+        self.add(message, None)
+    }
+
+    /// Print a line above the bars without disturbing them
+    pub fn println(&self, line: &str) {
+        let mut state = self.lock();
+        if state.terminal {
+            state.redraw(&[line.to_string()]);
+        } else {
+            state.write(&format!("{line}\n"));
+        }
+    }
+
+    fn add(&self, message: &str, total: Option<u64>) -> ProgressBar {
+        let mut state = self.lock();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.bars.push(BarState {
+            id,
+            message: message.to_string(),
+            position: 0,
+            total,
+            frame: 0,
+            last_log: None,
+        });
+        state.refresh(id, true);
+
+        ProgressBar {
+            id,
+            state: Arc::clone(&self.state),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        lock(&self.state)
+    }
+}
+
+/// A progress bar or spinner shown by a [`Progress`] display
+///
+/// A bar that is dropped before being finished is finished as it stands.
+pub struct ProgressBar {
+    id: usize,
+    state: Arc<Mutex<State>>,
+}
+
+impl ProgressBar {
+    /// Advance the bar by `delta`
+    pub fn inc(&self, delta: u64) {
+        self.update(|bar| bar.position = bar.position.saturating_add(delta));
+    }
+
+    /// Move the bar to the position
+    pub fn set_position(&self, position: u64) {
+        self.update(|bar| bar.position = position);
+    }
+
+    /// Replace the message shown next to the bar
+    pub fn set_message(&self, message: &str) {
+        self.update(|bar| bar.message = message.to_string());
+    }
+
+    /// Advance the spinner animation without changing the position
+    pub fn tick(&self) {
+        self.update(|_| ());
+    }
+
+    /// Get the current position of the bar, which is zero once it is finished
+    #[must_use]
+    pub fn position(&self) -> u64 {
+        let state = lock(&self.state);
+        state
+            .bars
+            .iter()
+            .find(|bar| bar.id == self.id)
+            .map_or(0, |bar| bar.position)
+    }
+
+    /// Advance the spinner animation on its own at the interval until the bar is finished
+    pub fn enable_steady_tick(&self, interval: Duration) {
+        let id = self.id;
+        let state = Arc::clone(&self.state);
+
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+
+            let mut state = lock(&state);
+            match state.bars.iter_mut().find(|bar| bar.id == id) {
+                Some(bar) => bar.frame += 1,
+                None => break,
+            }
+            state.refresh(id, false);
+        });
+    }
+
+    /// Fill the bar and stop updating it, leaving its last line on the terminal
+    pub fn finish(&self) {
+        self.close(true);
+    }
+
+    /// Replace the message then finish the bar
+    pub fn finish_with_message(&self, message: &str) {
+        self.set_message(message);
+        self.finish();
+    }
+
+    /// Stop updating the bar, filling it first if `fill` is set
+    ///
+    /// A bar that is not filled was dropped before finishing, such as when a command fails midway, which is reported
+    /// as aborted when the stream is not a terminal.
+    fn close(&self, fill: bool) {
+        let mut state = lock(&self.state);
+        let Some(index) = state.bars.iter().position(|bar| bar.id == self.id) else {
+            return;
+        };
+        let mut bar = state.bars.remove(index);
+        if let (true, Some(total)) = (fill, bar.total) {
+            bar.position = total;
+        }
+
+        if state.terminal {
+            let line = state.finished_line(&bar);
+            state.redraw(&[line]);
+        } else if fill {
+            state.write(&format!("{}: done\n", bar.message));
+        } else {
+            state.write(&format!("{}: aborted\n", bar.message));
+        }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut BarState)) {
+        let mut state = lock(&self.state);
+        if let Some(bar) = state.bars.iter_mut().find(|bar| bar.id == self.id) {
+            f(bar);
+            bar.frame += 1;
+            state.refresh(self.id, false);
+        }
+    }
+}
+
+impl Drop for ProgressBar {
+    fn drop(&mut self) {
+        self.close(false);
+    }
+}
+
+impl State {
+    /// Show the change to a bar, unless it was shown too recently and `force` is not set
+    fn refresh(&mut self, id: usize, force: bool) {
+        let now = Instant::now();
+
+        if self.terminal {
+            if force || self.last_draw.is_none_or(|last| now - last >= REDRAW_DELAY) {
+                self.redraw(&[]);
+            }
+            return;
+        }
+
+        let interval = self.interval;
+        let Some(bar) = self.bars.iter_mut().find(|bar| bar.id == id) else {
+            return;
+        };
+        if force || bar.last_log.is_none_or(|last| now - last >= interval) {
+            bar.last_log = Some(now);
+            let line = match bar.total {
+                Some(total) => format!(
+                    "{}: {}/{total} ({}%)\n",
+                    bar.message,
+                    bar.position,
+                    percent(bar.position, total)
+                ),
+                None => format!("{}...\n", bar.message),
+            };
+            self.write(&line);
+        }
+    }
+
+    /// Redraw the bars on the terminal, printing the lines above them
+    fn redraw(&mut self, lines: &[String]) {
+        let mut text = String::new();
+
+        // Move back to the first line drawn previously and clear everything below it
+        if self.drawn > 0 {
+            let _ = write!(text, "\x1b[{}A", self.drawn);
+        }
+        text.push_str("\r\x1b[J");
+
+        for line in lines {
+            text.push_str(line);
+            text.push('\n');
+        }
+        for bar in &self.bars {
+            text.push_str(&self.line(bar));
+            text.push('\n');
+        }
+
+        self.drawn = self.bars.len();
+        self.last_draw = Some(Instant::now());
+        self.write(&text);
+    }
+
+    /// Render the line of a running bar, such as `Downloading [████░░░░] 2/4  50%`
+    fn line(&self, bar: &BarState) -> String {
+        let Some(total) = bar.total else {
+            let frame = FRAMES[bar.frame % FRAMES.len()].to_string();
+            let message = truncate(&bar.message, self.width.saturating_sub(2));
+            return format!("{} {message}", self.paint(&frame, |theme| theme.info));
+        };
+
+        let counter = format!(
+            "{}/{total} {:>3}%",
+            bar.position,
+            percent(bar.position, total)
+        );
+        // The message takes at most half of the line, the bar fills what is left between it and the counter
+        let message = truncate(&bar.message, self.width / 2);
+        let bar_width = self
+            .width
+            .saturating_sub(display_width(&message) + display_width(&counter) + 4)
+            .min(MAX_BAR_WIDTH);
+
+        if bar_width == 0 {
+            return truncate(&format!("{message} {counter}"), self.width);
+        }
+
+        let filled = usize::try_from(
+            u128::from(bar.position.min(total)) * bar_width as u128 / u128::from(total.max(1)),
+        )
+        .unwrap_or(bar_width);
+        let filled = if total == 0 { bar_width } else { filled };
+
+        format!(
+            "{message} [{}{}] {counter}",
+            self.paint(&"█".repeat(filled), |theme| theme.success),
+            "░".repeat(bar_width - filled)
+        )
+    }
+
+    /// Render the line left on the terminal by a finished bar
+    fn finished_line(&self, bar: &BarState) -> String {
+        if bar.total.is_some() {
+            return self.line(bar);
+        }
+
+        let message = truncate(&bar.message, self.width.saturating_sub(2));
+        format!("{} {message}", self.paint("✔", |theme| theme.success))
+    }
+
+    fn paint(&self, text: &str, style: fn(&Theme) -> ansi_term::Style) -> String {
+        match &self.theme {
+            Some(theme) => style(theme).paint(text).to_string(),
+            None => text.to_string(),
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        if self.hidden {
+            return;
+        }
+        let _ = self.writer.write_all(text.as_bytes());
+        let _ = self.writer.flush();
+    }
+}
+
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Get the position as a percentage of the total, which is complete for an empty total
+fn percent(position: u64, total: u64) -> u64 {
+    // Multiplying a large position by 100 overflows 64 bits
+    (u128::from(position.min(total)) * 100)
+        .checked_div(u128::from(total))
+        .and_then(|percent| u64::try_from(percent).ok())
+        .unwrap_or(100)
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use super::*;
+
+    #[test]
+    fn percentages() {
+        assert_eq!(percent(1, 3), 33);
+        assert_eq!(percent(5, 4), 100);
+        assert_eq!(percent(0, 0), 100);
+        assert_eq!(percent(u64::MAX / 2, u64::MAX), 49);
+        assert_eq!(percent(u64::MAX, u64::MAX), 100);
+    }
+
+    #[test]
+    fn log_lines() {
+        let output = Output::captured();
+        let progress = Progress::new(&output).set_interval(Duration::from_secs(30));
+
+        let bar = progress.bar("Downloading", 4);
+        bar.inc(2);
+        let spinner = progress.spinner("Resolving");
+        progress.println("fetched index");
+        drop(spinner);
+        bar.finish();
+        bar.inc(1);
+
+        assert_eq!(
+            output.captured_text(Stream::Stderr),
+            "Downloading: 0/4 (0%)\nResolving...\nfetched index\nResolving: aborted\nDownloading: done\n"
+        );
+
+        let output = Output::captured();
+        let progress = Progress::new(&output).set_interval(Duration::ZERO);
+        progress.bar("Building", 2).inc(1);
+
+        assert_eq!(
+            output.captured_text(Stream::Stderr),
+            "Building: 0/2 (0%)\nBuilding: 1/2 (50%)\nBuilding: aborted\n"
+        );
+
+        let output = Output::captured();
+        Progress::new(&output)
+            .set_hidden(true)
+            .bar("Hidden", 1)
+            .inc(1);
+        assert_eq!(output.captured_text(Stream::Stderr), "");
+    }
+
+    #[test]
+    fn terminal_lines() {
+        let output = Output::captured();
+        let progress = Progress::new(&output).set_terminal(true).set_width(31);
+
+        let bar = progress.bar("Downloading", 4);
+        let spinner = progress.spinner("Resolving");
+        spinner.finish_with_message("Resolved");
+
+        assert_eq!(
+            output.captured_text(Stream::Stderr),
+            concat!(
+                "\r\x1b[JDownloading [░░░░░░░░] 0/4   0%\n",
+                "\x1b[1A\r\x1b[JDownloading [░░░░░░░░] 0/4   0%\n⠋ Resolving\n",
+                "\x1b[2A\r\x1b[J✔ Resolved\nDownloading [░░░░░░░░] 0/4   0%\n",
+            )
+        );
+
+        bar.set_position(2);
+        assert_eq!(bar.position(), 2);
+        let state = lock(&progress.state);
+        assert_eq!(
+            state.line(&state.bars[0]),
+            "Downloading [████░░░░] 2/4  50%"
+        );
+    }
+}
@@ -0,0 +1,712 @@
+//! Part of the Dock application that asks the user questions interactively
+//!
+//! Prompts are created from the context with [`Context::prompt`](crate::context::Context::prompt) and written to the
+//! standard error stream, so the standard output of a command can still be piped. They fail with
+//! [`Error::NoInput`] when the standard input is not a terminal or the `--no-input` global flag is passed, while the
+//! `-y/--yes` global flag answers confirmations without asking. Both flags are registered by
+//! [`App::enable_prompts`](crate::App::enable_prompts).
+//!
+//! ```rs,no_run
+//! let prompt = ctx.prompt();
+//!
+//! if prompt.confirm("Deploy to production?", false)? {
+//!     let region = prompt.select("Region", &["eu-west", "us-east"])?;
+//!     let token = prompt.password("Token")?;
+//!     // --snip--
+//! }
+//! ```
+//!
+//! Selection lists are navigated with the arrow keys on Unix terminals and answered by number elsewhere, where
+//! passwords cannot be asked as they would be echoed. Pressing `Ctrl-C` or `Escape` in a list or `Ctrl-C` while typing
+//! a password fails with [`Error::Interrupted`].
+
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    io::{self, BufRead, IsTerminal, Write},
+    str::FromStr,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use ansi_term::Style;
+
+use crate::{
+    arg::Arg,
+    color::Stream,
+    error::{Error, Result},
+    output::Output,
+    theme::Theme,
+};
+
+/// Identifier of the global flag answering confirmations without asking
+pub const YES: &str = "yes";
+/// Identifier of the global flag making prompts fail instead of asking
+pub const NO_INPUT: &str = "no-input";
+
+/// Get the global flags controlling whether prompts are asked
+#[must_use]
+pub fn args() -> Vec<Arg> {
+    vec![
+        Arg::new(YES)
+            .set_short('y')
+            .set_long("yes")
+            .set_help("Answer yes to every confirmation")
+            .set_takes_value(false),
+        Arg::new(NO_INPUT)
+            .set_long("no-input")
+            .set_help("Fail instead of asking questions")
+            .set_takes_value(false),
+    ]
+}
+
+/// Answers given to prompts in order instead of reading the terminal, such as in tests
+#[derive(Debug, Clone, Default)]
+pub struct Answers(Arc<Mutex<VecDeque<String>>>);
+
+impl Answers {
+    /// Create the answers given to the prompts in order
+    #[must_use]
+    pub fn new(answers: Vec<String>) -> Self {
+        Self(Arc::new(Mutex::new(answers.into())))
+    }
+
+    /// Take the next answer, if any is left
+    pub(crate) fn next(&self) -> Option<String> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop_front()
+    }
+}
+
+/// Asks the user questions on behalf of a command
+pub struct Prompter {
+    /// Whether questions can be asked
+    interactive: bool,
+    /// Whether confirmations are answered with yes without asking
+    assume_yes: bool,
+    /// Answers used instead of reading the terminal
+    answers: Option<Answers>,
+    /// Destination of the questions
+    output: Output,
+    /// Styles of the questions, if colors are enabled
+    theme: Option<Theme>,
+}
+
+impl Prompter {
+    /// Create a prompter reading the terminal, or the answers if some are given
+    pub(crate) fn new(output: Output, answers: Option<Answers>) -> Self {
+        Self {
+            interactive: answers.is_some() || io::stdin().is_terminal(),
+            assume_yes: false,
+            answers,
+            output,
+            theme: None,
+        }
+    }
+
+    /// Property setter
+    ///
+    /// Sets whether questions can be asked
+    #[must_use]
+    pub fn set_interactive(mut self, interactive: bool) -> Self {
+        self.interactive = interactive;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets whether confirmations are answered with yes without asking
+    #[must_use]
+    pub fn set_assume_yes(mut self, assume_yes: bool) -> Self {
+        self.assume_yes = assume_yes;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the styles of the questions, or `None` to print them without colors
+    #[must_use]
+    pub fn set_theme(mut self, theme: Option<Theme>) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Whether questions can be asked
+    #[must_use]
+    pub fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
+    /// Ask a yes or no question, returning the default if the answer is empty
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoInput`] if input is not interactive and confirmations are not assumed.
+    pub fn confirm(&self, message: &str, default: bool) -> Result<bool> {
+        if self.assume_yes {
+            return Ok(true);
+        }
+        self.ensure_interactive(message)?;
+
+        let hint = if default { "[Y/n]" } else { "[y/N]" };
+        loop {
+            self.ask(message, Some(hint));
+
+            match self.read_line(message)?.trim().to_lowercase().as_str() {
+                "" => return Ok(default),
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                _ => self.invalid("please answer yes or no"),
+            }
+        }
+    }
+
+    /// Ask for a line of text
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoInput`] if input is not interactive.
+    pub fn input(&self, message: &str) -> Result<String> {
+        self.input_with(message, |_| Ok(()))
+    }
+
+    /// Ask for a line of text, asking again until the validator accepts it
+    ///
+    /// ```rs,no_run
+    /// let name = prompt.input_with("Project name", |name| {
+    ///     if name.is_empty() {
+    ///         Err("the name cannot be empty".to_string())
+    ///     } else {
+    ///         Ok(())
+    ///     }
+    /// })?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoInput`] if input is not interactive.
+    pub fn input_with(
+        &self,
+        message: &str,
+        validate: impl Fn(&str) -> std::result::Result<(), String>,
+    ) -> Result<String> {
+        self.ensure_interactive(message)?;
+
+        loop {
+            self.ask(message, None);
+            let answer = self.read_line(message)?;
+
+            match validate(&answer) {
+                Ok(()) => return Ok(answer),
+                Err(reason) => self.invalid(&reason),
+            }
+        }
+    }
+
+    /// Ask for a value, asking again until it can be converted into `T`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoInput`] if input is not interactive.
+    pub fn parse<T>(&self, message: &str) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.ensure_interactive(message)?;
+
+        loop {
+            self.ask(message, None);
+
+            match self.read_line(message)?.parse() {
+                Ok(value) => return Ok(value),
+                Err(err) => self.invalid(&err.to_string()),
+            }
+        }
+    }
+
+    /// Ask for a secret without echoing it
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoInput`] if input is not interactive, [`Error::Interrupted`] if `Ctrl-C` is pressed, or an
+    /// error on platforms where the secret cannot be hidden.
+    pub fn password(&self, message: &str) -> Result<String> {
+        self.ensure_interactive(message)?;
+        self.ask(message, None);
+
+        if self.answers.is_some() {
+            return self.read_line(message);
+        }
+
+        let password = terminal::read_hidden().map_err(|err| match err.kind() {
+            io::ErrorKind::Interrupted => Error::Interrupted,
+            _ => Error::Custom(err.to_string()),
+        })?;
+        self.write("\n");
+        password.ok_or_else(|| Error::NoInput(message.to_string()))
+    }
+
+    /// Ask to choose one of the items, returning its index
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoInput`] if input is not interactive.
+    pub fn select<T: Display>(&self, message: &str, items: &[T]) -> Result<usize> {
+        self.choose(message, items, false)
+            .map(|chosen| chosen.first().copied().unwrap_or_default())
+    }
+
+    /// Ask to choose any number of the items, returning their indices
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NoInput`] if input is not interactive.
+    pub fn multi_select<T: Display>(&self, message: &str, items: &[T]) -> Result<Vec<usize>> {
+        self.choose(message, items, true)
+    }
+
+    fn choose<T: Display>(&self, message: &str, items: &[T], multiple: bool) -> Result<Vec<usize>> {
+        self.ensure_interactive(message)?;
+        if items.is_empty() {
+            return Err(Error::Custom(format!("nothing to choose for '{message}'")));
+        }
+
+        let items = items
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>();
+
+        if self.answers.is_none() && terminal::SUPPORTS_KEYS {
+            self.choose_with_keys(message, &items, multiple)
+        } else {
+            self.choose_by_number(message, &items, multiple)
+        }
+    }
+
+    /// List the items with numbers and read the chosen numbers or items separated by commas
+    fn choose_by_number(
+        &self,
+        message: &str,
+        items: &[String],
+        multiple: bool,
+    ) -> Result<Vec<usize>> {
+        for (i, item) in items.iter().enumerate() {
+            self.write(&format!("  {}) {item}\n", i + 1));
+        }
+
+        let hint = if multiple {
+            "(numbers separated by commas)"
+        } else {
+            "(number)"
+        };
+        loop {
+            self.ask(message, Some(hint));
+            let answer = self.read_line(message)?;
+
+            let chosen = answer
+                .split(',')
+                .map(str::trim)
+                .filter(|choice| !choice.is_empty())
+                .map(|choice| {
+                    items.iter().position(|item| item == choice).or_else(|| {
+                        choice
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|number| (1..=items.len()).contains(number))
+                            .map(|number| number - 1)
+                    })
+                })
+                .collect::<Option<Vec<usize>>>();
+
+            match chosen {
+                Some(chosen) if multiple || chosen.len() == 1 => return Ok(chosen),
+                _ => self.invalid(&format!("please choose a number from 1 to {}", items.len())),
+            }
+        }
+    }
+
+    /// Move a cursor over the items with the arrow keys, toggling items with space if `multiple` is set
+    fn choose_with_keys(
+        &self,
+        message: &str,
+        items: &[String],
+        multiple: bool,
+    ) -> Result<Vec<usize>> {
+        let hint = if multiple {
+            "(arrows to move, space to toggle, enter to confirm)"
+        } else {
+            "(arrows to move, enter to confirm)"
+        };
+        self.ask(message, Some(hint));
+        self.write("\n");
+
+        let mut cursor = 0;
+        let mut toggled = vec![false; items.len()];
+        let keys = terminal::Keys::new().map_err(|err| Error::Custom(err.to_string()))?;
+
+        loop {
+            self.draw_items(items, cursor, multiple.then_some(toggled.as_slice()));
+
+            match keys.read().map_err(|err| Error::Custom(err.to_string()))? {
+                terminal::Key::Up => cursor = cursor.checked_sub(1).unwrap_or(items.len() - 1),
+                terminal::Key::Down => cursor = (cursor + 1) % items.len(),
+                terminal::Key::Space if multiple => toggled[cursor] = !toggled[cursor],
+                terminal::Key::Enter => break,
+                terminal::Key::Interrupt => {
+                    drop(keys);
+                    self.write("\n");
+                    return Err(Error::Interrupted);
+                }
+                _ => {}
+            }
+
+            // Move back up over the items so they are redrawn in place
+            self.write(&format!("\x1b[{}A", items.len()));
+        }
+        drop(keys);
+
+        let chosen = if multiple {
+            (0..items.len()).filter(|&i| toggled[i]).collect()
+        } else {
+            vec![cursor]
+        };
+
+        // Replace the question and the items with the answer
+        self.write(&format!("\x1b[{}A\x1b[J", items.len() + 1));
+        self.ask(message, None);
+        let answer = chosen
+            .iter()
+            .map(|&i| items[i].as_str())
+            .collect::<Vec<&str>>()
+            .join(", ");
+        self.write(&format!("{}\n", self.paint(&answer, |theme| theme.command)));
+
+        Ok(chosen)
+    }
+
+    fn draw_items(&self, items: &[String], cursor: usize, toggled: Option<&[bool]>) {
+        for (i, item) in items.iter().enumerate() {
+            let pointer = if i == cursor { '>' } else { ' ' };
+            let checkbox = match toggled {
+                Some(toggled) if toggled[i] => "[x] ",
+                Some(_) => "[ ] ",
+                None => "",
+            };
+            let line = format!("{pointer} {checkbox}{item}");
+
+            let line = if i == cursor {
+                self.paint(&line, |theme| theme.command)
+            } else {
+                line
+            };
+            self.write(&format!("\x1b[2K{line}\n"));
+        }
+    }
+
+    fn ensure_interactive(&self, message: &str) -> Result {
+        if self.interactive {
+            Ok(())
+        } else {
+            Err(Error::NoInput(message.to_string()))
+        }
+    }
+
+    /// Write the question, followed by the hint if one is given
+    fn ask(&self, message: &str, hint: Option<&str>) {
+        let mut question = format!("{} {message} ", self.paint("?", |theme| theme.info));
+        if let Some(hint) = hint {
+            question = format!("{question}{} ", self.paint(hint, |theme| theme.placeholder));
+        }
+        self.write(&question);
+    }
+
+    fn invalid(&self, reason: &str) {
+        self.write(&format!(
+            "{} {reason}\n",
+            self.paint("error:", |theme| theme.error)
+        ));
+    }
+
+    /// Read a line of input, echoing scripted answers so the transcript reads like a terminal session
+    fn read_line(&self, message: &str) -> Result<String> {
+        let line = if let Some(answers) = &self.answers {
+            answers
+                .next()
+                .inspect(|answer| self.write(&format!("{answer}\n")))
+        } else {
+            let mut line = String::new();
+            match io::stdin().lock().read_line(&mut line) {
+                Ok(0) | Err(_) => None,
+                Ok(_) => Some(line),
+            }
+        };
+
+        line.map(|line| line.trim_end_matches(['\r', '\n']).to_string())
+            .ok_or_else(|| Error::NoInput(message.to_string()))
+    }
+
+    fn paint(&self, text: &str, style: impl Fn(&Theme) -> Style) -> String {
+        match &self.theme {
+            Some(theme) => style(theme).paint(text).to_string(),
+            None => text.to_string(),
+        }
+    }
+
+    fn write(&self, text: &str) {
+        let mut writer = self.output.writer(Stream::Stderr);
+        let _ = write!(writer, "{text}");
+        let _ = writer.flush();
+    }
+}
+
+/// Reads keys from the terminal without echoing them
+#[cfg(unix)]
+pub(crate) mod terminal {
+    use std::io::{self, Read};
+
+    /// Whether selection lists can be navigated with the arrow keys
+    pub const SUPPORTS_KEYS: bool = true;
+
+    /// A key pressed while choosing items
+    pub enum Key {
+        Up,
+        Down,
+        Space,
+        Enter,
+        Interrupt,
+        Other,
+    }
+
+    /// Disables echo, line buffering and signals on the standard input stream until dropped
+    ///
+    /// Signals are disabled so that an interrupt restores the terminal before exiting instead of leaving it without echo.
+    pub struct Keys {
+        original: libc::termios,
+        raw: libc::termios,
+    }
+
+    impl Keys {
+        pub fn new() -> io::Result<Self> {
+            // SAFETY: termios is a plain C struct for which zeroed memory is valid, and it is filled by tcgetattr
+            let mut attrs: libc::termios = unsafe { std::mem::zeroed() };
+
+            // SAFETY: the pointer is valid for the duration of the call
+            if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &raw mut attrs) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let original = attrs;
+
+            attrs.c_lflag &= !(libc::ECHO | libc::ICANON | libc::ISIG);
+            attrs.c_cc[libc::VMIN] = 1;
+            attrs.c_cc[libc::VTIME] = 0;
+            set_attrs(&attrs)?;
+
+            Ok(Self {
+                original,
+                raw: attrs,
+            })
+        }
+
+        /// Read a single byte of input
+        pub fn byte() -> io::Result<u8> {
+            let mut byte = [0];
+            io::stdin().lock().read_exact(&mut byte)?;
+            Ok(byte[0])
+        }
+
+        /// Read the two bytes of the sequence following an escape byte, returning `None` for a lone escape key
+        ///
+        /// The bytes of a sequence arrive together, so the escape key is alone if nothing follows it shortly.
+        pub fn sequence(&self) -> io::Result<Option<(u8, u8)>> {
+            let mut attrs = self.raw;
+            attrs.c_cc[libc::VMIN] = 0;
+            attrs.c_cc[libc::VTIME] = SEQUENCE_TIMEOUT;
+            set_attrs(&attrs)?;
+
+            let sequence = Self::byte().and_then(|first| Ok((first, Self::byte()?)));
+            set_attrs(&self.raw)?;
+
+            match sequence {
+                Ok(sequence) => Ok(Some(sequence)),
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+                Err(err) => Err(err),
+            }
+        }
+
+        pub fn read(&self) -> io::Result<Key> {
+            Ok(match Self::byte()? {
+                b'\r' | b'\n' => Key::Enter,
+                b' ' => Key::Space,
+                b'k' => Key::Up,
+                b'j' => Key::Down,
+                0x03 | 0x04 => Key::Interrupt,
+                0x1b => match self.sequence()? {
+                    Some((b'[', b'A')) => Key::Up,
+                    Some((b'[', b'B')) => Key::Down,
+                    Some(_) => Key::Other,
+                    None => Key::Interrupt,
+                },
+                _ => Key::Other,
+            })
+        }
+    }
+
+    /// Tenths of a second to wait for the rest of an escape sequence
+    const SEQUENCE_TIMEOUT: libc::cc_t = 1;
+
+    fn set_attrs(attrs: &libc::termios) -> io::Result<()> {
+        // SAFETY: the pointer is valid for the duration of the call
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, attrs) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    impl Drop for Keys {
+        fn drop(&mut self) {
+            let _ = set_attrs(&self.original);
+        }
+    }
+
+    /// Read a line without echoing it, returning `None` on end of input
+    pub fn read_hidden() -> io::Result<Option<String>> {
+        let _keys = Keys::new()?;
+        let mut line = vec![];
+
+        loop {
+            match Keys::byte() {
+                Ok(b'\r' | b'\n') => break,
+                Ok(0x03) => return Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted")),
+                Ok(0x04) if line.is_empty() => return Ok(None),
+                Ok(0x7f | 0x08) => {
+                    line.pop();
+                }
+                Ok(byte) => line.push(byte),
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(Some(String::from_utf8_lossy(&line).to_string()))
+    }
+}
+
+/// Fallback for platforms where echo cannot be disabled, on which items are chosen by number and secrets are not asked
+#[cfg(not(unix))]
+#[allow(dead_code)]
+pub(crate) mod terminal {
+    use std::io;
+
+    pub const SUPPORTS_KEYS: bool = false;
+
+    pub enum Key {
+        Up,
+        Down,
+        Space,
+        Enter,
+        Interrupt,
+        Other,
+    }
+
+    pub struct Keys;
+
+    // The methods take the same receivers as on Unix
+    #[allow(clippy::unused_self)]
+    impl Keys {
+        pub fn new() -> io::Result<Self> {
+            Err(unsupported())
+        }
+
+        pub fn byte() -> io::Result<u8> {
+            Err(unsupported())
+        }
+
+        pub fn sequence(&self) -> io::Result<Option<(u8, u8)>> {
+            Err(unsupported())
+        }
+
+        pub fn read(&self) -> io::Result<Key> {
+            Err(unsupported())
+        }
+    }
+
+    fn unsupported() -> io::Error {
+        io::Error::new(io::ErrorKind::Unsupported, "key input is not supported")
+    }
+
+    /// Refuse to read a secret, which would be echoed as it cannot be hidden on this platform
+    pub fn read_hidden() -> io::Result<Option<String>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "secrets cannot be read without echoing them on this platform",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use super::*;
+
+    fn prompter(answers: &[&str]) -> (Prompter, Output) {
+        let output = Output::captured();
+        let answers = Answers::new(answers.iter().map(ToString::to_string).collect());
+
+        (Prompter::new(output.clone(), Some(answers)), output)
+    }
+
+    #[test]
+    fn scripted_prompts() {
+        let (prompt, output) = prompter(&["maybe", "", "eight", "8", ""]);
+
+        assert!(prompt.confirm("Deploy?", true).unwrap());
+        assert_eq!(prompt.parse::<u8>("Jobs").unwrap(), 8);
+        assert_eq!(
+            prompt
+                .input_with("Name", |name| if name.is_empty() {
+                    Err("required".to_string())
+                } else {
+                    Ok(())
+                })
+                .unwrap_err(),
+            Error::NoInput("Name".to_string())
+        );
+        assert!(output
+            .captured_text(Stream::Stderr)
+            .starts_with("? Deploy? [Y/n] maybe\nerror: please answer yes or no\n"));
+
+        let (prompt, output) = prompter(&["b, 3", "2", "hunter2"]);
+        assert_eq!(
+            prompt.multi_select("Pick", &["a", "b", "c"]).unwrap(),
+            vec![1, 2]
+        );
+        assert_eq!(prompt.select("Pick", &["a", "b", "c"]).unwrap(), 1);
+        assert_eq!(prompt.password("Token").unwrap(), "hunter2");
+        assert!(output.captured_text(Stream::Stderr).contains("  3) c\n"));
+        assert!(prompt.input("More").is_err());
+    }
+
+    #[test]
+    fn non_interactive_prompts() {
+        let (prompt, _) = prompter(&[]);
+        let prompt = prompt.set_interactive(false);
+
+        assert_eq!(
+            prompt.input("Name"),
+            Err(Error::NoInput("Name".to_string()))
+        );
+        assert!(prompt
+            .set_assume_yes(true)
+            .confirm("Deploy?", false)
+            .unwrap());
+    }
+
+    #[test]
+    fn interrupted() {
+        assert_eq!(Error::Interrupted.to_string(), "interrupted");
+        assert_eq!(Error::Interrupted.exit_code(), 130);
+        assert!(!Error::Interrupted.is_usage());
+    }
+}
@@ -0,0 +1,489 @@
+//! Part of the Dock application that runs commands in an interactive session
+//!
+//! [`App::repl`] shows a prompt, reads a line, splits it the way a shell would and invokes the matching command until
+//! `exit` is entered or the input ends. Errors are reported without ending the session, `help` prints the help
+//! message as usual and `history` lists the lines entered so far.
+//!
+//! On Unix terminals, previous lines are recalled with the up and down arrows, the cursor is moved with the left and
+//! right arrows, `Home` and `End`, and command names are completed with tab. Lines piped to the standard input are
+//! run in order without showing the prompt.
+//!
+//! The history is kept in memory unless a file is given with [`App::set_repl_history`], in which case the lines of
+//! previous sessions are loaded from it and every line entered is appended to it.
+//!
+//! ```rs,no_run
+//! App::from_crate()
+//!     .register_command(build)
+//!     .set_repl_prompt("dock> ")
+//!     .repl()
+//! ```
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, BufRead, IsTerminal, Write},
+    path::{Path, PathBuf},
+};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    color::Stream,
+    context::Environment,
+    error::{Error, Result},
+    output::Output,
+    prompt::{terminal, Answers},
+    App,
+};
+
+/// Lines ending the session
+const EXIT: [&str; 2] = ["exit", "quit"];
+/// Line listing the lines entered so far
+const HISTORY: &str = "history";
+/// Built-in lines completed along with the command names
+const BUILTINS: [&str; 3] = ["exit", "help", HISTORY];
+
+impl App {
+    /// Start an interactive session in which every line entered invokes a command
+    ///
+    /// The session ends when `exit` or `quit` is entered or the input ends, such as with `Ctrl-D`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input cannot be read. Errors of the invoked commands are printed instead.
+    pub fn repl(self) -> Result {
+        self.repl_with(&Environment::default())
+    }
+
+    /// Start an interactive session in the given environment, which every command is invoked in
+    ///
+    /// Only the program name is taken from the argv of the environment, the arguments of every invocation coming from
+    /// the line entered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input cannot be read. Errors of the invoked commands are printed instead.
+    pub fn repl_with(self, env: &Environment) -> Result {
+        let program = self.program_from(env);
+        #[cfg(feature = "plugins")]
+        let app = {
+            let report = self.report(&program, env.clone());
+            self.with_plugins(&report)
+        };
+        #[cfg(not(feature = "plugins"))]
+        let app = self;
+
+        let colored = app.color.resolve(
+            |name| env.env_var(name).map(str::to_string),
+            || app.output.is_terminal(Stream::Stderr),
+        );
+        let prompt = app
+            .repl_prompt
+            .clone()
+            .unwrap_or_else(|| format!("{program}> "));
+
+        let mut editor = Editor {
+            prompt: if colored {
+                app.theme.command.paint(prompt).to_string()
+            } else {
+                prompt
+            },
+            output: app.output.clone(),
+            answers: app.answers.clone(),
+            interactive: io::stdin().is_terminal(),
+            commands: app
+                .commands
+                .iter()
+                .filter(|command| !command.disabled())
+                .map(|command| command.name())
+                .collect(),
+            history: app
+                .repl_history
+                .as_deref()
+                .map(load_history)
+                .unwrap_or_default(),
+            history_file: app.repl_history.clone(),
+        };
+
+        while let Some(line) = editor
+            .read_line()
+            .map_err(|err| Error::Custom(err.to_string()))?
+        {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            editor.record(line);
+
+            if EXIT.contains(&line) {
+                break;
+            }
+            if line == HISTORY {
+                editor.print_history();
+                continue;
+            }
+
+            let _ = app.clone().execute_line(&program, line, env.clone());
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the lines of a session from scripted answers, the terminal or piped input
+struct Editor {
+    /// Prompt shown before every line
+    prompt: String,
+    /// Destination of the prompt and the lines echoed
+    output: Output,
+    /// Lines used instead of reading the standard input, such as in tests
+    answers: Option<Answers>,
+    /// Whether the standard input is a terminal
+    interactive: bool,
+    /// Names of the commands completed with tab
+    commands: Vec<String>,
+    /// Lines entered so far, oldest first
+    history: Vec<String>,
+    /// File the lines entered are appended to
+    history_file: Option<PathBuf>,
+}
+
+impl Editor {
+    /// Read the next line, returning `None` when the input ends
+    fn read_line(&self) -> io::Result<Option<String>> {
+        if let Some(answers) = &self.answers {
+            // Scripted lines are echoed so the transcript reads like a terminal session
+            return Ok(answers.next().inspect(|line| {
+                self.write(&format!("{}{line}\n", self.prompt));
+            }));
+        }
+
+        if self.interactive && terminal::SUPPORTS_KEYS {
+            return self.edit();
+        }
+        if self.interactive {
+            self.write(&self.prompt);
+        }
+
+        let mut line = String::new();
+        match io::stdin().lock().read_line(&mut line)? {
+            0 => Ok(None),
+            _ => Ok(Some(line.trim_end_matches(['\r', '\n']).to_string())),
+        }
+    }
+
+    /// Read a line from the terminal key by key, recalling history and moving the cursor with the arrow keys and
+    /// completing with tab
+    fn edit(&self) -> io::Result<Option<String>> {
+        let keys = terminal::Keys::new()?;
+        let mut line = Line::default();
+        // Bytes of a character that is not complete yet
+        let mut pending = vec![];
+        let mut recalled = self.history.len();
+
+        self.write(&self.prompt);
+
+        loop {
+            match terminal::Keys::byte()? {
+                b'\r' | b'\n' => break,
+                0x03 => {
+                    line = Line::default();
+                    recalled = self.history.len();
+                    self.write("^C\n");
+                }
+                0x04 if line.text.is_empty() => {
+                    self.write("\n");
+                    return Ok(None);
+                }
+                0x7f | 0x08 => line.backspace(),
+                0x01 => line.cursor = 0,
+                0x05 => line.cursor = line.text.len(),
+                // Only the end of the line is completed, the rest being arguments already typed
+                b'\t' if line.cursor == line.text.len() => {
+                    match complete(&line.text, &self.commands) {
+                        Completion::Line(completed) => line = Line::from(completed),
+                        Completion::Candidates(candidates) => {
+                            self.write(&format!("\n{}\n", candidates.join("  ")));
+                        }
+                        Completion::None => {}
+                    }
+                }
+                0x1b => match keys.sequence()? {
+                    Some((b'[', b'A')) if recalled > 0 => {
+                        recalled -= 1;
+                        line = Line::from(self.history[recalled].clone());
+                    }
+                    Some((b'[', b'B')) if recalled < self.history.len() => {
+                        recalled += 1;
+                        line = Line::from(self.history.get(recalled).cloned().unwrap_or_default());
+                    }
+                    Some((b'[', b'C')) => line.right(),
+                    Some((b'[', b'D')) => line.left(),
+                    Some((b'[' | b'O', b'H')) => line.cursor = 0,
+                    Some((b'[' | b'O', b'F')) => line.cursor = line.text.len(),
+                    // `Delete` is sent as `ESC [ 3 ~`
+                    Some((b'[', b'3')) if terminal::Keys::byte()? == b'~' => line.delete(),
+                    _ => {}
+                },
+                byte if byte >= 0x20 => {
+                    pending.push(byte);
+                    if let Ok(text) = std::str::from_utf8(&pending) {
+                        line.insert(text);
+                        pending.clear();
+                    } else if pending.len() >= 4 {
+                        pending.clear();
+                    }
+                }
+                _ => {}
+            }
+
+            self.write(&format!("\r\x1b[K{}{}", self.prompt, line.text));
+            // The cursor is left at the end of the line, so it is moved back over the text following it
+            let behind = line.text[line.cursor..].width();
+            if behind > 0 {
+                self.write(&format!("\x1b[{behind}D"));
+            }
+        }
+
+        self.write("\n");
+        Ok(Some(line.text))
+    }
+
+    /// Add a line to the history, appending it to the history file if there is one
+    fn record(&mut self, line: &str) {
+        self.history.push(line.to_string());
+
+        if let Some(path) = &self.history_file {
+            // A history that cannot be saved should not end the session
+            let _ = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{line}"));
+        }
+    }
+
+    fn print_history(&self) {
+        let mut writer = self.output.writer(Stream::Stdout);
+        for (index, line) in self.history.iter().enumerate() {
+            let _ = writeln!(writer, "{:>4}  {line}", index + 1);
+        }
+    }
+
+    fn write(&self, text: &str) {
+        let mut writer = self.output.writer(Stream::Stderr);
+        let _ = write!(writer, "{text}");
+        let _ = writer.flush();
+    }
+}
+
+/// Load the lines of previous sessions from a history file, which is empty if the file does not exist yet
+fn load_history(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .map(|text| {
+            text.lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// A line being edited on the terminal
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Line {
+    /// Text entered so far
+    text: String,
+    /// Byte offset of the cursor in the text, which is always on a character boundary
+    cursor: usize,
+}
+
+impl From<String> for Line {
+    /// Create a line with the cursor at its end
+    fn from(text: String) -> Self {
+        Self {
+            cursor: text.len(),
+            text,
+        }
+    }
+}
+
+impl Line {
+    /// Insert text at the cursor, leaving the cursor after it
+    fn insert(&mut self, text: &str) {
+        self.text.insert_str(self.cursor, text);
+        self.cursor += text.len();
+    }
+
+    /// Remove the character before the cursor
+    fn backspace(&mut self) {
+        if let Some(character) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= character.len_utf8();
+            self.text.remove(self.cursor);
+        }
+    }
+
+    /// Remove the character under the cursor
+    fn delete(&mut self) {
+        if self.cursor < self.text.len() {
+            self.text.remove(self.cursor);
+        }
+    }
+
+    /// Move the cursor one character to the left
+    fn left(&mut self) {
+        if let Some(character) = self.text[..self.cursor].chars().next_back() {
+            self.cursor -= character.len_utf8();
+        }
+    }
+
+    /// Move the cursor one character to the right
+    fn right(&mut self) {
+        if let Some(character) = self.text[self.cursor..].chars().next() {
+            self.cursor += character.len_utf8();
+        }
+    }
+}
+
+/// Result of completing a line
+#[derive(Debug, PartialEq, Eq)]
+enum Completion {
+    /// Nothing matches the word being typed
+    None,
+    /// The line with the word being typed completed
+    Line(String),
+    /// The words matching the word being typed, which cannot be completed further
+    Candidates(Vec<String>),
+}
+
+/// Complete the command name being typed, which is the first word of the line or the word following `help`
+fn complete(line: &str, commands: &[String]) -> Completion {
+    let (head, word, words) = match line.split_once(' ') {
+        None => (
+            "",
+            line,
+            BUILTINS
+                .iter()
+                .map(ToString::to_string)
+                .chain(commands.iter().cloned())
+                .collect::<Vec<String>>(),
+        ),
+        Some(("help", word)) if !word.contains(' ') => ("help ", word, commands.to_vec()),
+        Some(_) => return Completion::None,
+    };
+
+    let mut candidates = words
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(word))
+        .collect::<Vec<String>>();
+    candidates.sort();
+    candidates.dedup();
+
+    match candidates.as_slice() {
+        [] => Completion::None,
+        [candidate] => Completion::Line(format!("{head}{candidate} ")),
+        [first, rest @ ..] => {
+            let prefix = rest.iter().fold(first.as_str(), |prefix, candidate| {
+                let common = prefix
+                    .char_indices()
+                    .zip(candidate.chars())
+                    .find(|((_, a), b)| a != b)
+                    .map_or(prefix.len().min(candidate.len()), |((index, _), _)| index);
+                &prefix[..common]
+            });
+
+            if prefix.len() > word.len() {
+                Completion::Line(format!("{head}{prefix}"))
+            } else {
+                Completion::Candidates(candidates)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use super::*;
+
+    #[test]
+    fn completion() {
+        let commands = ["build", "bench", "deploy"].map(String::from);
+
+        assert_eq!(
+            complete("d", &commands),
+            Completion::Line("deploy ".to_string())
+        );
+        assert_eq!(
+            complete("b", &commands),
+            Completion::Candidates(vec!["bench".to_string(), "build".to_string()])
+        );
+        assert_eq!(
+            complete("h", &commands),
+            Completion::Candidates(vec!["help".to_string(), "history".to_string()])
+        );
+        assert_eq!(
+            complete("help bu", &commands),
+            Completion::Line("help build ".to_string())
+        );
+        assert_eq!(complete("build --rel", &commands), Completion::None);
+        assert_eq!(complete("x", &commands), Completion::None);
+
+        let commands = ["deploy-app", "deploy-db"].map(String::from);
+        assert_eq!(
+            complete("de", &commands),
+            Completion::Line("deploy-".to_string())
+        );
+    }
+
+    #[test]
+    fn line_editing() {
+        let mut line = Line::from("buld".to_string());
+        line.left();
+        line.left();
+        line.insert("i");
+        assert_eq!(line.text, "build");
+        assert_eq!(line.cursor, 3);
+
+        line.right();
+        line.right();
+        line.right();
+        assert_eq!(line.cursor, 5);
+        line.insert(" é");
+        line.left();
+        line.backspace();
+        assert_eq!(line.text, "buildé");
+
+        line.delete();
+        assert_eq!(line, Line::from("build".to_string()));
+        line.cursor = 0;
+        line.backspace();
+        line.left();
+        assert_eq!(line.cursor, 0);
+        assert_eq!(line.text, "build");
+    }
+
+    #[test]
+    fn builtins() {
+        let mut app = App::new();
+        app.output = Output::captured();
+        app.answers = Some(Answers::new(
+            ["", "  history", "unknown", "exit", "history"]
+                .map(String::from)
+                .to_vec(),
+        ));
+
+        assert!(app
+            .clone()
+            .repl_with(&Environment::new().set_argv(["tool"]))
+            .is_ok());
+        assert_eq!(app.output.captured_text(Stream::Stdout), "   1  history\n");
+        assert!(app
+            .output
+            .captured_text(Stream::Stderr)
+            .starts_with("tool> \ntool>   history\ntool> unknown\n"));
+        assert!(app
+            .output
+            .captured_text(Stream::Stderr)
+            .ends_with("tool> exit\n"));
+    }
+}
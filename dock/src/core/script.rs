@@ -0,0 +1,243 @@
+//! Part of the Dock application that runs commands listed in a script, one per line
+//!
+//! Every line of a script is split the way a shell would and invokes the matching command, such as
+//! `deploy --env staging`. Blank lines and lines starting with `#` are skipped. The script stops at the first command
+//! that fails unless it keeps going, in which case every line runs and the failing lines are reported at the end.
+//!
+//! ```text
+//! # Release the staging environment
+//! build --release
+//! deploy --env staging
+//! ```
+//!
+//! Scripts are run with [`App::run_script`] or, once [`App::enable_scripts`] is called, with `tool --script ops.txt`,
+//! where `-` reads the script from the standard input. A script can run other scripts, up to a nesting depth of
+//! [`MAX_DEPTH`], which stops a script that runs itself.
+
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+};
+
+use crate::{
+    app::Feature,
+    arg::Arg,
+    context::Environment,
+    error::{Error, Result},
+    App,
+};
+
+/// Identifier of the global argument naming the script to run
+pub const SCRIPT: &str = "script";
+/// Identifier of the global flag running every line of a script even after a command fails
+pub const KEEP_GOING: &str = "keep-going";
+/// How many scripts can be nested, counting the outermost one
+pub const MAX_DEPTH: usize = 16;
+
+/// Get the global arguments running a script
+#[must_use]
+pub fn args() -> Vec<Arg> {
+    vec![
+        Arg::new(SCRIPT)
+            .set_long("script")
+            .set_value_name("FILE")
+            .set_help("Run the commands listed in the file, or in the standard input for '-'"),
+        Arg::new(KEEP_GOING)
+            .set_long("keep-going")
+            .set_help("Keep running the script after a command fails")
+            .set_takes_value(false),
+    ]
+}
+
+impl App {
+    /// Run the commands read from the reader, one per line
+    ///
+    /// ```rs,no_run
+    /// app().run_script(BufReader::new(File::open("ops.txt")?))
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Script`] naming the lines whose command failed, or an error if the script cannot be read.
+    pub fn run_script(self, reader: impl BufRead) -> Result {
+        self.run_script_with(reader, &Environment::default())
+    }
+
+    /// Run the commands read from the reader, one per line, in the given environment
+    ///
+    /// Only the program name is taken from the argv of the environment, the arguments of every invocation coming from
+    /// the lines of the script.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error::Script`] naming the lines whose command failed, or an error if the script cannot be read.
+    pub fn run_script_with(self, reader: impl BufRead, env: &Environment) -> Result {
+        let program = self.program_from(env);
+        let keep_going = self.features.contains(Feature::KeepGoing);
+        let report = self.report(&program, env.clone());
+
+        #[cfg(feature = "plugins")]
+        let app = self.with_plugins(&report);
+        #[cfg(not(feature = "plugins"))]
+        let app = self;
+
+        let result = app.script(&program, reader, env, keep_going);
+        if let Err(err) = &result {
+            report.print_error(err);
+        }
+
+        result
+    }
+
+    /// Run the script at the path, or the standard input for `-`
+    pub(crate) fn script_file(self, path: &str, env: &Environment, keep_going: bool) -> Result {
+        let program = self.program_from(env);
+
+        if path == "-" {
+            return self.script(&program, io::stdin().lock(), env, keep_going);
+        }
+
+        let file = File::open(path)
+            .map_err(|err| Error::Custom(format!("failed to read script '{path}': {err}")))?;
+        self.script(&program, BufReader::new(file), env, keep_going)
+    }
+
+    /// Run every line of the script, stopping at the first failure unless `keep_going` is set
+    fn script(
+        self,
+        program: &str,
+        reader: impl BufRead,
+        env: &Environment,
+        keep_going: bool,
+    ) -> Result {
+        if env.script_depth >= MAX_DEPTH {
+            return Err(Error::Custom(format!(
+                "scripts are nested more than {MAX_DEPTH} deep, such as a script running itself"
+            )));
+        }
+        let env = Environment {
+            script_depth: env.script_depth + 1,
+            ..env.clone()
+        };
+
+        let mut failed = vec![];
+        let mut first_error = None;
+
+        for (index, line) in reader.lines().enumerate() {
+            let number = index + 1;
+            let line = line.map_err(|err| {
+                Error::Custom(format!("failed to read line {number} of the script: {err}"))
+            })?;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Err(err) = self.clone().execute_line(program, line, env.clone()) {
+                failed.push(number);
+                first_error.get_or_insert(err);
+
+                if !keep_going {
+                    break;
+                }
+            }
+        }
+
+        match first_error {
+            Some(error) => Err(Error::Script {
+                lines: failed,
+                error: Box::new(error),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The error of a command passed along with a script, which would otherwise be ignored
+pub(crate) fn conflict(command: &str) -> Error {
+    Error::ConflictingArgument {
+        arg: "--script".to_string(),
+        with: command.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use std::{cell::RefCell, io::Cursor, rc::Rc};
+
+    use super::*;
+    use crate::{command::Command, context::Context, output::Output};
+
+    #[derive(Clone)]
+    struct Step(Rc<RefCell<Vec<String>>>);
+
+    impl Command for Step {
+        fn name(&self) -> String {
+            "step".to_string()
+        }
+        fn description(&self) -> String {
+            "Run a step".to_string()
+        }
+        fn disabled(&self) -> bool {
+            false
+        }
+        fn args(&self) -> Vec<Arg> {
+            vec![Arg::new("name").set_required(true)]
+        }
+        fn call(&self, ctx: &Context) -> Result {
+            let name = ctx.matches.value_of("name").unwrap_or_default();
+            self.0.borrow_mut().push(name.to_string());
+            if name == "fail" {
+                return Err(Error::Custom(format!("step {name} failed")));
+            }
+            Ok(())
+        }
+    }
+
+    const SCRIPT: &str = "# Release\n\nstep build\nstep fail\n  step 'deploy app'\nstep fail\n";
+
+    fn run(keep_going: bool) -> (Result, Vec<String>) {
+        let steps = Rc::new(RefCell::new(vec![]));
+        let mut app = App::new()
+            .register_command(Step(steps.clone()))
+            .set_keep_going(keep_going);
+        app.output = Output::captured();
+
+        let result =
+            app.run_script_with(Cursor::new(SCRIPT), &Environment::new().set_argv(["tool"]));
+        let steps = steps.borrow().clone();
+        (result, steps)
+    }
+
+    #[test]
+    fn stop_at_failure() {
+        assert_eq!(
+            run(false),
+            (
+                Err(Error::Script {
+                    lines: vec![4],
+                    error: Box::new(Error::Custom("step fail failed".to_string())),
+                }),
+                vec!["build".to_string(), "fail".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn keep_going() {
+        assert_eq!(
+            run(true),
+            (
+                Err(Error::Script {
+                    lines: vec![4, 6],
+                    error: Box::new(Error::Custom("step fail failed".to_string())),
+                }),
+                ["build", "fail", "deploy app", "fail"]
+                    .map(String::from)
+                    .to_vec()
+            )
+        );
+    }
+}
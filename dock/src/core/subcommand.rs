@@ -0,0 +1,138 @@
+//! The part of the application that handles command trees declared as enums
+//!
+//! An enum deriving `Subcommand` describes one command per variant. Each variant holds the group of arguments
+//! the command accepts, and the enum implements [`Execute`] to run the parsed variant.
+
+use std::marker::PhantomData;
+
+use crate::{
+    arg::{Arg, ArgMatches},
+    command::{Command, Example},
+    context::Context,
+    error::Result,
+};
+
+/// Describes the command formed from a single variant of a `Subcommand` enum
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant {
+    /// Name of the command
+    pub name: String,
+    /// Description of the command, taken from the doc comment of the variant
+    pub description: String,
+    /// Whether the command is disabled
+    pub disabled: bool,
+    /// Category the command is grouped under in the help message
+    pub category: Option<String>,
+    /// Example invocations shown in the help message of the command
+    pub examples: Vec<Example>,
+    /// Spec of the arguments held by the variant
+    pub args: Vec<Arg>,
+}
+
+/// The trait implemented by enums through `#[derive(Subcommand)]`.
+///
+/// ```rs,no_run
+/// #[derive(Subcommand)]
+/// enum Ops {
+///     /// Build the project
+///     Build(BuildArgs),
+///     /// Remove build artifacts
+///     Clean,
+/// }
+///
+/// impl Execute for Ops {
+///     fn execute(self, ctx: &Context) -> Result {
+///         // --snip--
+///     }
+/// }
+///
+/// App::from_crate()
+///     .register_subcommands::<Ops>()
+///     .run()
+/// ```
+pub trait Subcommand: Sized {
+    /// Get the commands described by the variants
+    fn variants() -> Vec<Variant>;
+
+    /// Construct the variant with the given command name from parsed matches
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name does not belong to a variant or its arguments cannot be converted.
+    fn from_matches(name: &str, matches: &ArgMatches) -> Result<Self>;
+}
+
+/// Runs a parsed `Subcommand` variant
+pub trait Execute {
+    /// Invoke the parsed variant
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the invocation fails.
+    fn execute(self, ctx: &Context) -> Result;
+}
+
+/// A command formed from a single variant of a `Subcommand` enum
+pub struct VariantCommand<T> {
+    variant: Variant,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> VariantCommand<T>
+where
+    T: Subcommand + Execute + 'static,
+{
+    /// Create a command for every variant of the enum
+    #[must_use]
+    pub fn all() -> Vec<Self> {
+        T::variants()
+            .into_iter()
+            .map(|variant| Self {
+                variant,
+                marker: PhantomData,
+            })
+            .collect()
+    }
+}
+
+impl<T> Clone for VariantCommand<T> {
+    fn clone(&self) -> Self {
+        Self {
+            variant: self.variant.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Command for VariantCommand<T>
+where
+    T: Subcommand + Execute + 'static,
+{
+    fn name(&self) -> String {
+        self.variant.name.clone()
+    }
+
+    fn description(&self) -> String {
+        self.variant.description.clone()
+    }
+
+    fn disabled(&self) -> bool {
+        self.variant.disabled
+    }
+
+    fn args(&self) -> Vec<Arg> {
+        self.variant.args.clone()
+    }
+
+    fn category(&self) -> Option<String> {
+        self.variant.category.clone()
+    }
+
+    fn examples(&self) -> Vec<Example> {
+        self.variant.examples.clone()
+    }
+
+    fn call(&self, ctx: &Context) -> Result {
+        T::from_matches(&self.variant.name, &ctx.matches)?.execute(ctx)
+    }
+}
@@ -0,0 +1,335 @@
+//! Part of the Dock application that renders tables for command output
+//!
+//! ```rs,no_run
+//! let table = Table::new()
+//!     .set_headers(vec!["NAME".to_string(), "REPLICAS".to_string()])
+//!     .set_align(1, Align::Right)
+//!     .add_row(vec!["api".to_string(), "3".to_string()]);
+//!
+//! ctx.print_table(&table)?;
+//! ```
+
+use ansi_term::Style;
+use unicode_width::UnicodeWidthChar;
+
+use crate::terminal::display_width;
+
+/// Marker appended to cells truncated to fit the table width
+const ELLIPSIS: char = '…';
+
+/// Gap between columns of a table without borders
+const GAP: usize = 2;
+
+/// Horizontal alignment of the cells of a column
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Align {
+    /// Align the cells to the left
+    #[default]
+    Left,
+    /// Align the cells to the right, such as for numbers
+    Right,
+    /// Center the cells
+    Center,
+}
+
+/// A table with optional headers, rendered with aligned columns
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    align: Vec<Align>,
+    border: bool,
+    width: Option<usize>,
+    header_style: Option<Style>,
+}
+
+impl Table {
+    /// Create an empty table without headers
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Property setter
+    ///
+    /// Sets the headers shown above the rows
+    #[must_use]
+    pub fn set_headers(mut self, headers: Vec<String>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Adds a row below the existing ones
+    #[must_use]
+    pub fn add_row(mut self, row: Vec<String>) -> Self {
+        self.rows.push(row);
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets all the rows, replacing the existing ones
+    #[must_use]
+    pub fn set_rows(mut self, rows: Vec<Vec<String>>) -> Self {
+        self.rows = rows;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the alignment of the column at the index
+    #[must_use]
+    pub fn set_align(mut self, column: usize, align: Align) -> Self {
+        if self.align.len() <= column {
+            self.align.resize(column + 1, Align::default());
+        }
+        self.align[column] = align;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets whether the table is drawn with borders
+    #[must_use]
+    pub fn set_border(mut self, border: bool) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the maximum width of the table in columns, beyond which the widest cells are truncated
+    #[must_use]
+    pub fn set_width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Property setter
+    ///
+    /// Sets the style the headers are painted with
+    #[must_use]
+    pub fn set_header_style(mut self, style: Style) -> Self {
+        self.header_style = Some(style);
+        self
+    }
+
+    /// The maximum width of the table, if one is set
+    #[must_use]
+    pub fn width(&self) -> Option<usize> {
+        self.width
+    }
+
+    /// Whether the table has neither headers nor rows
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty() && self.rows.is_empty()
+    }
+
+    /// Render the table into lines joined by newlines
+    #[must_use]
+    pub fn render(&self) -> String {
+        let widths = self.column_widths();
+        if widths.is_empty() {
+            return String::new();
+        }
+
+        let mut lines = vec![];
+
+        if self.border {
+            lines.push(Self::rule(&widths, '┌', '┬', '┐'));
+        }
+        if !self.headers.is_empty() {
+            lines.push(self.line(&self.headers, &widths, self.header_style));
+            if self.border {
+                lines.push(Self::rule(&widths, '├', '┼', '┤'));
+            }
+        }
+        for row in &self.rows {
+            lines.push(self.line(row, &widths, None));
+        }
+        if self.border {
+            lines.push(Self::rule(&widths, '└', '┴', '┘'));
+        }
+
+        lines.join("\n")
+    }
+
+    /// Get the width of every column, shrinking the widest ones until the table fits its maximum width
+    fn column_widths(&self) -> Vec<usize> {
+        let mut widths: Vec<usize> = vec![];
+
+        for row in std::iter::once(&self.headers).chain(&self.rows) {
+            for (i, cell) in row.iter().enumerate() {
+                let width = display_width(cell);
+                match widths.get_mut(i) {
+                    Some(max) => *max = (*max).max(width),
+                    None => widths.push(width),
+                }
+            }
+        }
+
+        if let Some(width) = self.width {
+            let separators = if self.border {
+                3 * widths.len() + 1
+            } else {
+                GAP * widths.len().saturating_sub(1)
+            };
+            let available = width.saturating_sub(separators);
+
+            while widths.iter().sum::<usize>() > available {
+                match widths.iter_mut().max() {
+                    Some(widest) if *widest > 1 => *widest -= 1,
+                    _ => break,
+                }
+            }
+        }
+
+        widths
+    }
+
+    fn line(&self, row: &[String], widths: &[usize], style: Option<Style>) -> String {
+        let cells = widths.iter().enumerate().map(|(i, width)| {
+            let cell = truncate(row.get(i).map_or("", String::as_str), *width);
+            let padded = pad(
+                &cell,
+                *width,
+                self.align.get(i).copied().unwrap_or_default(),
+            );
+
+            match style {
+                Some(style) => style.paint(padded).to_string(),
+                None => padded,
+            }
+        });
+
+        if self.border {
+            format!("│ {} │", cells.collect::<Vec<String>>().join(" │ "))
+        } else {
+            cells
+                .collect::<Vec<String>>()
+                .join(&" ".repeat(GAP))
+                .trim_end()
+                .to_string()
+        }
+    }
+
+    fn rule(widths: &[usize], left: char, middle: char, right: char) -> String {
+        let segments = widths
+            .iter()
+            .map(|width| "─".repeat(width + 2))
+            .collect::<Vec<String>>();
+
+        format!("{left}{}{right}", segments.join(&middle.to_string()))
+    }
+}
+
+/// Truncate the text to the width, marking the truncation with an ellipsis
+///
+/// ANSI escape sequences take no columns and are kept whole, and the style is reset after the ellipsis if the text
+/// was styled.
+pub(crate) fn truncate(text: &str, width: usize) -> String {
+    if display_width(text) <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    let mut truncated = String::new();
+    let mut truncated_width = 0;
+    let mut styled = false;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            styled = true;
+            truncated.push(c);
+            for c in chars.by_ref() {
+                truncated.push(c);
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+
+        let c_width = c.width().unwrap_or_default();
+        if truncated_width + c_width + 1 > width {
+            break;
+        }
+        truncated.push(c);
+        truncated_width += c_width;
+    }
+
+    truncated.push(ELLIPSIS);
+    if styled {
+        truncated.push_str("\u{1b}[0m");
+    }
+    truncated
+}
+
+fn pad(text: &str, width: usize, align: Align) -> String {
+    let space = width.saturating_sub(display_width(text));
+
+    match align {
+        Align::Left => format!("{text}{}", " ".repeat(space)),
+        Align::Right => format!("{}{text}", " ".repeat(space)),
+        Align::Center => format!(
+            "{}{text}{}",
+            " ".repeat(space / 2),
+            " ".repeat(space - space / 2)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use super::*;
+
+    fn services() -> Table {
+        Table::new()
+            .set_headers(vec!["NAME".to_string(), "REPLICAS".to_string()])
+            .add_row(vec!["api-gateway".to_string(), "3".to_string()])
+            .add_row(vec!["queue".to_string(), "12".to_string()])
+    }
+
+    #[test]
+    fn render_table() {
+        assert_eq!(
+            services().set_align(1, Align::Right).render(),
+            "NAME         REPLICAS\napi-gateway         3\nqueue              12"
+        );
+        assert_eq!(
+            services().set_border(true).render(),
+            [
+                "┌─────────────┬──────────┐",
+                "│ NAME        │ REPLICAS │",
+                "├─────────────┼──────────┤",
+                "│ api-gateway │ 3        │",
+                "│ queue       │ 12       │",
+                "└─────────────┴──────────┘",
+            ]
+            .join("\n")
+        );
+        assert_eq!(Table::new().render(), "");
+    }
+
+    #[test]
+    fn truncate_table() {
+        assert_eq!(
+            services().set_width(16).render(),
+            "NAME     REPLIC…\napi-ga…  3\nqueue    12"
+        );
+        assert_eq!(truncate("日本語", 4), "日…");
+        assert_eq!(truncate("queue", 0), "");
+
+        let colored = crate::Color::Green.paint("healthy").to_string();
+        assert_eq!(truncate(&colored, 7), colored);
+        assert_eq!(truncate(&colored, 4), "\u{1b}[32mhea…\u{1b}[0m");
+        assert_eq!(display_width(&truncate(&colored, 4)), 4);
+    }
+}
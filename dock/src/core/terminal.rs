@@ -0,0 +1,159 @@
+//! Part of the Dock application that handles terminal dimensions and text layout
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::{context::Environment, output::Output};
+
+/// Width used when the terminal width cannot be detected
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// Get the width of the terminal the output is written to, in columns
+///
+/// The `COLUMNS` variable of the environment takes precedence over the detected width of the standard output stream,
+/// which is not detected when the output is captured.
+#[must_use]
+pub fn width(env: &Environment, output: &Output) -> usize {
+    env.env_var("COLUMNS")
+        .and_then(|columns| columns.parse().ok())
+        .filter(|columns| *columns > 0)
+        .or_else(|| {
+            if output.is_captured() {
+                None
+            } else {
+                terminal_size::terminal_size().map(|(width, _)| usize::from(width.0))
+            }
+        })
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Get the height of the terminal the output is written to in rows, if it can be detected
+///
+/// The `LINES` variable of the environment takes precedence over the detected height of the standard output stream,
+/// which is not detected when the output is captured.
+#[must_use]
+pub fn height(env: &Environment, output: &Output) -> Option<usize> {
+    env.env_var("LINES")
+        .and_then(|lines| lines.parse().ok())
+        .filter(|lines| *lines > 0)
+        .or_else(|| {
+            if output.is_captured() {
+                None
+            } else {
+                terminal_size::terminal_size().map(|(_, height)| usize::from(height.0))
+            }
+        })
+}
+
+/// Get the number of columns the text occupies when displayed
+///
+/// Wide characters such as CJK ideographs count as two columns and ANSI escape sequences are ignored.
+#[must_use]
+pub fn display_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip the escape sequence up to and including its final letter
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            width += c.width().unwrap_or_default();
+        }
+    }
+
+    width
+}
+
+/// Wrap the text into lines no wider than `width` columns
+///
+/// Lines are broken at whitespace, and words wider than `width` are split.
+#[must_use]
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = vec![];
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut line_width = 0;
+
+        for word in paragraph.split_whitespace() {
+            let word_width = display_width(word);
+
+            if line_width > 0 && line_width + 1 + word_width > width {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0;
+            }
+
+            if line_width > 0 {
+                line.push(' ');
+                line_width += 1;
+            }
+
+            for c in word.chars() {
+                let c_width = c.width().unwrap_or_default();
+
+                if line_width > 0 && line_width + c_width > width {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                }
+
+                line.push(c);
+                line_width += c_width;
+            }
+        }
+
+        lines.push(line);
+    }
+
+    if lines.is_empty() {
+        lines.push(String::new());
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use super::*;
+
+    #[test]
+    fn unicode_width() {
+        assert_eq!(display_width("dock"), 4);
+        assert_eq!(display_width("日本"), 4);
+        assert_eq!(
+            display_width(&crate::Color::Green.paint("dock").to_string()),
+            4
+        );
+    }
+
+    #[test]
+    fn word_wrap() {
+        assert_eq!(
+            wrap("The simple, fast and powerful parser", 12),
+            vec!["The simple,", "fast and", "powerful", "parser"]
+        );
+        assert_eq!(wrap("abcdefgh", 3), vec!["abc", "def", "gh"]);
+        assert_eq!(wrap("日本語", 4), vec!["日本", "語"]);
+        assert_eq!(wrap("", 10), vec![""]);
+    }
+
+    #[test]
+    fn captured_width() {
+        let output = Output::captured();
+
+        assert_eq!(width(&Environment::new(), &output), DEFAULT_WIDTH);
+        assert_eq!(
+            width(&Environment::new().set_var("COLUMNS", "30"), &output),
+            30
+        );
+        assert_eq!(
+            width(&Environment::new().set_var("COLUMNS", "wide"), &output),
+            DEFAULT_WIDTH
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//! Part of the Dock application that runs applications in tests without spawning binaries
+//!
+//! This module is enabled by the `testing` feature, which is meant to be turned on for dev-dependencies only.
+//!
+//! ```rs,no_run
+//! #[test]
+//! fn build() {
+//!     app()
+//!         .test(["tool", "build", "-v"])
+//!         .assert_success()
+//!         .assert_stdout_contains("Finished");
+//! }
+//! ```
+
+use std::{ffi::OsString, path::PathBuf};
+
+use crate::{
+    color::Stream,
+    context::{Context, Environment},
+    error::{Error, Result},
+    output::Output,
+    prompt::Answers,
+    App,
+};
+
+/// Environment variable which, when set, makes snapshot assertions overwrite the stored snapshots
+pub const UPDATE_SNAPSHOTS: &str = "DOCK_UPDATE_SNAPSHOTS";
+
+/// The outcome of running an application through [`App::test`]
+pub struct TestRun {
+    /// Text written to the standard output stream
+    pub stdout: String,
+    /// Text written to the standard error stream
+    pub stderr: String,
+    /// Exit code the process would have terminated with
+    pub exit_code: i32,
+    /// Result returned by the application
+    pub result: Result,
+    /// Context the command was invoked with, unless the help message was printed or parsing failed
+    pub context: Option<Context>,
+}
+
+impl App {
+    /// Run the application with the given arguments, the first of which is the program name, capturing its output
+    ///
+    /// The application runs in an empty [`Environment`] so that the variables of the process do not affect it.
+    pub fn test<I, T>(self, args: I) -> TestRun
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        self.test_with(Environment::new().set_argv(args))
+    }
+
+    /// Property setter
+    ///
+    /// Sets the answers given in order to the prompts of the command instead of reading the terminal
+    #[must_use]
+    pub fn set_answers(mut self, answers: &[&str]) -> Self {
+        self.answers = Some(Answers::new(
+            answers.iter().map(ToString::to_string).collect(),
+        ));
+        self
+    }
+
+    /// Run an interactive session of the given program reading the lines in order, capturing its output
+    ///
+    /// The lines are echoed after the prompt on the standard error stream, like they would be on a terminal.
+    pub fn test_repl(mut self, program: &str, lines: &[&str]) -> TestRun {
+        let output = Output::captured();
+        self.output = output.clone();
+
+        let result = self
+            .set_answers(lines)
+            .repl_with(&Environment::new().set_argv([program]));
+
+        TestRun {
+            stdout: output.captured_text(Stream::Stdout),
+            stderr: output.captured_text(Stream::Stderr),
+            exit_code: result.as_ref().err().map_or(0, Error::exit_code),
+            result,
+            context: None,
+        }
+    }
+
+    /// Run the application in the given environment, whose argv holds the arguments to parse, capturing its output
+    pub fn test_with(mut self, env: Environment) -> TestRun {
+        let output = Output::captured();
+        self.output = output.clone();
+
+        let (result, context) = self.execute(env);
+
+        TestRun {
+            stdout: output.captured_text(Stream::Stdout),
+            stderr: output.captured_text(Stream::Stderr),
+            exit_code: result.as_ref().err().map_or(0, Error::exit_code),
+            result,
+            context,
+        }
+    }
+}
+
+// Assertions are called for their panics, returning the run only to allow chaining them
+#[allow(clippy::must_use_candidate)]
+impl TestRun {
+    /// Assert that the application succeeded
+    ///
+    /// # Panics
+    ///
+    /// Panics if the application returned an error.
+    #[track_caller]
+    pub fn assert_success(&self) -> &Self {
+        assert!(
+            self.result.is_ok(),
+            "expected success, got {:?}\nstderr:\n{}",
+            self.result,
+            self.stderr
+        );
+        self
+    }
+
+    /// Assert that the application failed
+    ///
+    /// # Panics
+    ///
+    /// Panics if the application succeeded.
+    #[track_caller]
+    pub fn assert_failure(&self) -> &Self {
+        assert!(
+            self.result.is_err(),
+            "expected failure\nstdout:\n{}",
+            self.stdout
+        );
+        self
+    }
+
+    /// Assert that the process would have terminated with the exit code
+    ///
+    /// # Panics
+    ///
+    /// Panics if the exit code differs.
+    #[track_caller]
+    pub fn assert_exit_code(&self, exit_code: i32) -> &Self {
+        assert_eq!(
+            self.exit_code, exit_code,
+            "unexpected exit code\nstderr:\n{}",
+            self.stderr
+        );
+        self
+    }
+
+    /// Assert that the standard output contains the text
+    ///
+    /// # Panics
+    ///
+    /// Panics if the text was not written to the standard output stream.
+    #[track_caller]
+    pub fn assert_stdout_contains(&self, text: &str) -> &Self {
+        assert!(
+            self.stdout.contains(text),
+            "expected stdout to contain {text:?}\nstdout:\n{}",
+            self.stdout
+        );
+        self
+    }
+
+    /// Assert that the standard error contains the text
+    ///
+    /// # Panics
+    ///
+    /// Panics if the text was not written to the standard error stream.
+    #[track_caller]
+    pub fn assert_stderr_contains(&self, text: &str) -> &Self {
+        assert!(
+            self.stderr.contains(text),
+            "expected stderr to contain {text:?}\nstderr:\n{}",
+            self.stderr
+        );
+        self
+    }
+
+    /// Assert that the standard output matches the snapshot with the given name
+    ///
+    /// # Panics
+    ///
+    /// Panics if the standard output differs from the snapshot.
+    #[track_caller]
+    pub fn assert_stdout_snapshot(&self, name: &str) -> &Self {
+        assert_snapshot(name, &self.stdout);
+        self
+    }
+}
+
+/// Assert that the text matches the snapshot stored in `tests/snapshots/<name>.snap` of the crate under test
+///
+/// The snapshot is written instead if it does not exist yet or the `DOCK_UPDATE_SNAPSHOTS` environment variable
+/// is set, in which case the new snapshot should be reviewed and committed.
+///
+/// # Panics
+///
+/// Panics if the text differs from the snapshot or the snapshot cannot be read or written.
+#[track_caller]
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path = std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join("tests")
+        .join("snapshots")
+        .join(format!("{name}.snap"));
+
+    if path.exists() && std::env::var_os(UPDATE_SNAPSHOTS).is_none() {
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|err| panic!("failed to read snapshot {}: {err}", path.display()));
+
+        assert_eq!(
+            expected, actual,
+            "snapshot {name} does not match, set {UPDATE_SNAPSHOTS}=1 to update it"
+        );
+    } else {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap_or_else(|err| {
+                panic!(
+                    "failed to create snapshot directory {}: {err}",
+                    parent.display()
+                )
+            });
+        }
+        std::fs::write(&path, actual)
+            .unwrap_or_else(|err| panic!("failed to write snapshot {}: {err}", path.display()));
+    }
+}
@@ -0,0 +1,116 @@
+//! Part of the Dock application that handles the styling of help and error messages
+
+use ansi_term::{Color, Style};
+
+/// Holds the styles used when rendering colored help, error and status messages
+///
+/// A theme is set on the application with [`App::set_theme`](crate::App::set_theme).
+/// Several presets are provided, and individual styles can be overridden as the fields are public.
+///
+/// ```rs,no_run
+/// App::from_crate()
+///     .set_theme(Theme {
+///         heading: Color::Purple.bold(),
+///         ..Theme::classic()
+///     })
+///     .run()
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// Style of the application name, version and section headings
+    pub heading: Style,
+    /// Style of command names
+    pub command: Style,
+    /// Style of argument names such as `--verbose`
+    pub flag: Style,
+    /// Style of value placeholders such as `<PATH>`
+    pub placeholder: Style,
+    /// Style of the `error:` label
+    pub error: Style,
+    /// Style of the `warning:` label
+    pub warning: Style,
+    /// Style of the `success:` label
+    pub success: Style,
+    /// Style of the `info:` label
+    pub info: Style,
+    /// Style of the `debug:` label of log records
+    pub debug: Style,
+    /// Style of the `trace:` label of log records
+    pub trace: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+impl Theme {
+    /// The default theme, with blue headings and green command names
+    #[must_use]
+    pub fn classic() -> Self {
+        Self {
+            heading: Color::Blue.normal(),
+            command: Color::Green.normal(),
+            flag: Color::Cyan.normal(),
+            placeholder: Color::Yellow.normal(),
+            error: Color::Red.bold(),
+            warning: Color::Yellow.bold(),
+            success: Color::Green.bold(),
+            info: Color::Cyan.bold(),
+            debug: Color::Blue.bold(),
+            trace: Color::Purple.bold(),
+        }
+    }
+
+    /// A theme with brighter, bold colors
+    #[must_use]
+    pub fn vivid() -> Self {
+        Self {
+            heading: Color::Purple.bold().underline(),
+            command: Color::Cyan.bold(),
+            flag: Color::Green.bold(),
+            placeholder: Color::Yellow.italic(),
+            error: Color::Red.bold().underline(),
+            warning: Color::Yellow.bold(),
+            success: Color::Green.bold(),
+            info: Color::Blue.bold(),
+            debug: Color::Purple.bold(),
+            trace: Color::White.dimmed(),
+        }
+    }
+
+    /// A theme using only text attributes, for terminals with limited color support
+    #[must_use]
+    pub fn monochrome() -> Self {
+        Self {
+            heading: Style::new().bold().underline(),
+            command: Style::new().bold(),
+            flag: Style::new().bold(),
+            placeholder: Style::new().italic(),
+            error: Style::new().bold().reverse(),
+            warning: Style::new().bold(),
+            success: Style::new().bold(),
+            info: Style::new(),
+            debug: Style::new().italic(),
+            trace: Style::new().dimmed(),
+        }
+    }
+
+    /// A theme without any styling
+    #[must_use]
+    pub fn plain() -> Self {
+        Self {
+            heading: Style::new(),
+            command: Style::new(),
+            flag: Style::new(),
+            placeholder: Style::new(),
+            error: Style::new(),
+            warning: Style::new(),
+            success: Style::new(),
+            info: Style::new(),
+            debug: Style::new(),
+            trace: Style::new(),
+        }
+    }
+}
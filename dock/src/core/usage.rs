@@ -0,0 +1,156 @@
+//! Part of the Dock application that synthesizes usage lines from argument specs
+//!
+//! A usage line such as `tool build [OPTIONS] --target <TARGET> <INPUT> [FILES]...` lists the optional named
+//! arguments as `[OPTIONS]`, followed by the required named arguments and the positional arguments in order.
+
+use crate::{arg::Arg, command::Command, theme::Theme};
+
+/// Get the placeholder shown for the value of an argument, such as `<PATH>`
+#[must_use]
+pub fn placeholder(arg: &Arg) -> String {
+    format!(
+        "<{}>",
+        arg.value_name
+            .clone()
+            .unwrap_or_else(|| arg.id.to_uppercase())
+    )
+}
+
+/// Get the placeholder shown for a positional argument, such as `<TARGET>` or `[FILES]...`
+#[must_use]
+pub fn positional(arg: &Arg) -> String {
+    let name = arg
+        .value_name
+        .clone()
+        .unwrap_or_else(|| arg.id.to_uppercase());
+    let mut positional = if arg.required {
+        format!("<{name}>")
+    } else {
+        format!("[{name}]")
+    };
+
+    if arg.multiple {
+        positional.push_str("...");
+    }
+
+    positional
+}
+
+/// Get the usage line of the application, listing the global arguments and the command placeholder
+#[must_use]
+pub fn app_usage(program: &str, globals: &[Arg], theme: Option<&Theme>) -> String {
+    let mut parts = vec![paint(program, theme.map(|theme| theme.command))];
+
+    if globals.iter().any(|arg| !arg.is_positional()) {
+        parts.push(paint("[OPTIONS]", theme.map(|theme| theme.flag)));
+    }
+    parts.push(paint("<COMMAND>", theme.map(|theme| theme.placeholder)));
+
+    parts.join(" ")
+}
+
+/// Remove the arguments whose identifier was already declared, such as groups shared between commands
+#[must_use]
+pub fn unique(args: Vec<Arg>) -> Vec<Arg> {
+    let mut unique: Vec<Arg> = vec![];
+
+    for arg in args {
+        if !unique.iter().any(|seen| seen.id == arg.id) {
+            unique.push(arg);
+        }
+    }
+
+    unique
+}
+
+/// Get the usage line of a command from its argument spec
+#[must_use]
+pub fn command_usage(program: &str, command: &dyn Command, theme: Option<&Theme>) -> String {
+    let args = unique(command.args());
+    let mut parts = vec![paint(
+        &format!("{program} {}", command.name()),
+        theme.map(|theme| theme.command),
+    )];
+
+    if args.iter().any(|arg| !arg.is_positional() && !arg.required) {
+        parts.push(paint("[OPTIONS]", theme.map(|theme| theme.flag)));
+    }
+
+    for arg in args
+        .iter()
+        .filter(|arg| !arg.is_positional() && arg.required)
+    {
+        let mut part = paint(&arg.display_name(), theme.map(|theme| theme.flag));
+
+        if arg.takes_value {
+            part = format!(
+                "{part} {}",
+                paint(&placeholder(arg), theme.map(|theme| theme.placeholder))
+            );
+        }
+        parts.push(part);
+    }
+
+    for arg in args.iter().filter(|arg| arg.is_positional()) {
+        parts.push(paint(
+            &positional(arg),
+            theme.map(|theme| theme.placeholder),
+        ));
+    }
+
+    parts.join(" ")
+}
+
+fn paint(text: &str, style: Option<ansi_term::Style>) -> String {
+    match style {
+        Some(style) => style.paint(text).to_string(),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod app_tests {
+
+    use super::*;
+    use crate::{context::Context, error::Result};
+
+    #[derive(Clone)]
+    struct Build;
+
+    impl Command for Build {
+        fn name(&self) -> String {
+            "build".to_string()
+        }
+        fn description(&self) -> String {
+            "Build the project".to_string()
+        }
+        fn disabled(&self) -> bool {
+            false
+        }
+        fn args(&self) -> Vec<Arg> {
+            vec![
+                Arg::new("jobs").set_short('j').set_long("jobs"),
+                Arg::new("profile").set_long("profile").set_required(true),
+                Arg::new("target").set_required(true),
+                Arg::new("files").set_multiple(true),
+            ]
+        }
+        fn call(&self, _ctx: &Context) -> Result {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn command_usage_line() {
+        let build: Box<dyn Command> = Box::new(Build);
+
+        assert_eq!(
+            build.usage("tool"),
+            "tool build [OPTIONS] --profile <PROFILE> <TARGET> [FILES]..."
+        );
+        assert_eq!(
+            app_usage("tool", &[Arg::new("verbose").set_short('v')], None),
+            "tool [OPTIONS] <COMMAND>"
+        );
+    }
+}
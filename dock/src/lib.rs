@@ -6,4 +6,4 @@ pub mod core;
 
 pub use crate::core::*;
 
-pub use ansi_term::Color;
+pub use ansi_term::{Color, Style};
@@ -0,0 +1,15 @@
+//! Plugin loaded by the plugin tests, registering a single command
+
+use dock::{context::Context, plugin::Registrar};
+use dock_codegen::command;
+
+#[command(description = "Greet from a plugin")]
+fn greet(ctx: &Context) {
+    ctx.success("hello from a plugin");
+}
+
+fn register(registrar: &mut Registrar) {
+    registrar.register_command(greet);
+}
+
+dock::export_plugin!(register);